@@ -0,0 +1,74 @@
+// Structs
+use crate::Env;
+// Filesystem
+use std::fs;
+use std::path::Path;
+// Error Handling
+use miette::Result;
+use pipelight_error::{LibError, PipelightError};
+
+/**
+ * Parse a dotenv file content.
+ * - one KEY=value per line,
+ * - blank lines and lines starting with '#' are ignored,
+ * - an optional leading "export " is ignored,
+ * - values can be surrounded by single or double quotes.
+ */
+pub fn parse_dotenv(string: &str) -> Result<Env, String> {
+    let mut env = Env::new();
+    for (i, line) in string.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!(
+                "line {}: expected KEY=value, found \"{}\"",
+                i + 1,
+                line
+            ));
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(format!("line {}: invalid variable name \"{}\"", i + 1, key));
+        }
+        let mut value = value.trim();
+        for quote in ['"', '\''] {
+            if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+                value = &value[1..value.len() - 1];
+            }
+        }
+        env.insert(key.to_owned(), value.to_owned());
+    }
+    Ok(env)
+}
+
+/**
+ * Merge the dotenv file variables under the inline ones.
+ * Inline variables override the file ones.
+ */
+pub(super) fn merge_env_file(
+    env: &mut Option<Env>,
+    env_file: &Option<String>,
+    root: &Path,
+) -> Result<()> {
+    if let Some(env_file) = env_file {
+        let path = root.join(env_file);
+        let string = fs::read_to_string(&path).map_err(|e| {
+            PipelightError::from(LibError {
+                message: format!("Couldn't read the env file \"{}\".", path.display()),
+                help: e.to_string(),
+            })
+        })?;
+        let mut merged = parse_dotenv(&string).map_err(|e| {
+            PipelightError::from(LibError {
+                message: format!("Couldn't parse the env file \"{}\".", path.display()),
+                help: e,
+            })
+        })?;
+        merged.extend(env.clone().unwrap_or_default());
+        *env = Some(merged);
+    }
+    Ok(())
+}
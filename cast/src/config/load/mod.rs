@@ -13,6 +13,8 @@ use std::path::Path;
 // Tests
 mod test;
 
+mod env;
+mod paths;
 mod rules;
 mod typescript;
 
@@ -50,7 +52,10 @@ impl Config {
             FileType::Hcl => Config::hcl(file_path)?,
             // FileType::Pkl => Config::pkl(file_path)?,
         };
-        Ok(config.strict_check()?)
+        let mut config = config.strict_check()?;
+        let root = Path::new(file_path).parent().unwrap_or(Path::new("."));
+        config.resolve_paths(root)?;
+        Ok(config)
     }
     /**
     Returns a Config struct from a provided json file path.
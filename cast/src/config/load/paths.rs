@@ -0,0 +1,141 @@
+// Structs
+use crate::{Config, Fallback, StepOrParallel};
+// Filesystem
+use std::path::{Path, PathBuf};
+// Error Handling
+use miette::Result;
+use pipelight_error::{LibError, PipelightError};
+
+use super::env::merge_env_file;
+
+/**
+ * Ensure the step working directory exists
+ * and replace it with its absolute path.
+ */
+fn resolve_cwd(cwd: &mut Option<String>, step: &str, root: &Path) -> Result<()> {
+    if let Some(dir) = cwd {
+        let path = root.join(&dir);
+        if !path.is_dir() {
+            return Err(PipelightError::from(LibError {
+                message: format!(
+                    "The working directory \"{}\" of step \"{}\" doesn't exist.",
+                    dir, step
+                ),
+                help: format!(
+                    "Step cwd is relative to the config file directory ({})",
+                    root.display()
+                ),
+            })
+            .into());
+        }
+        let path = path.canonicalize().map_err(|e| {
+            PipelightError::from(LibError {
+                message: format!("Couldn't resolve the working directory \"{}\".", dir),
+                help: e.to_string(),
+            })
+        })?;
+        *cwd = Some(path.display().to_string());
+    }
+    Ok(())
+}
+
+/**
+ * Expand a leading ~ and ensure the secret file lives outside of the project,
+ * so that it can't be committed along with the config.
+ */
+fn resolve_secret_file(file: &mut Option<String>, secret: &str, root: &Path) -> Result<()> {
+    if let Some(path) = file {
+        let expanded = match path.strip_prefix("~/") {
+            Some(rest) => PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(rest),
+            None => PathBuf::from(&path),
+        };
+        let root = root.canonicalize().unwrap_or(root.to_path_buf());
+        if !expanded.is_absolute() || expanded.starts_with(&root) {
+            return Err(PipelightError::from(LibError {
+                message: format!(
+                    "The file \"{}\" of secret \"{}\" must live outside of the project.",
+                    path, secret
+                ),
+                help: "Use an absolute path or a path relative to your home, ex: \"~/.secrets/token\"."
+                    .to_owned(),
+            })
+            .into());
+        }
+        *file = Some(expanded.display().to_string());
+    }
+    Ok(())
+}
+
+impl Config {
+    /**
+     * Resolve the paths declared relatively to the config file directory.
+     * - every env_file is loaded and merged into the corresponding env,
+     * - every step cwd is checked and made absolute,
+     * - every secret file is checked and made absolute.
+     */
+    pub fn resolve_paths(&mut self, root: &Path) -> Result<()> {
+        for (name, secret) in self.secrets.iter_mut().flatten() {
+            resolve_secret_file(&mut secret.file, name, root)?;
+        }
+        if let Some(options) = &mut self.options {
+            merge_env_file(&mut options.env, &options.env_file, root)?;
+        }
+        for pipeline in self.pipelines.iter_mut().flatten() {
+            if let Some(options) = &mut pipeline.options {
+                merge_env_file(&mut options.env, &options.env_file, root)?;
+            }
+            for step in &mut pipeline.steps {
+                step.resolve_paths(root)?;
+            }
+            if let Some(fallback) = &mut pipeline.fallback {
+                fallback.resolve_paths(root)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Fallback {
+    fn resolve_paths(&mut self, root: &Path) -> Result<()> {
+        for steps in [
+            &mut self.on_started,
+            &mut self.on_failure,
+            &mut self.on_success,
+            &mut self.on_abortion,
+            &mut self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for step in steps {
+                step.resolve_paths(root)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StepOrParallel {
+    fn resolve_paths(&mut self, root: &Path) -> Result<()> {
+        let (steps, fallback) = match self {
+            StepOrParallel::Step(step) => (vec![step.as_mut()], None),
+            StepOrParallel::Parallel(parallel) => (
+                parallel.parallel.iter_mut().collect(),
+                parallel.fallback.as_mut(),
+            ),
+        };
+        if let Some(fallback) = fallback {
+            fallback.resolve_paths(root)?;
+        }
+        for step in steps {
+            if let Some(options) = &mut step.options {
+                merge_env_file(&mut options.env, &options.env_file, root)?;
+                resolve_cwd(&mut options.cwd, &step.name, root)?;
+            }
+            if let Some(fallback) = &mut step.fallback {
+                fallback.resolve_paths(root)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,12 @@
-use crate::Config;
+use crate::{Config, Fallback, Matrix, Parallel, Pipeline, Step, StepOrParallel};
+// Collections
+use std::collections::HashMap;
+use std::ptr;
+// Durations and sizes
+use pipelight_exec::dates::convert::human_duration_to_std_duration;
+use pipelight_exec::human_size_to_bytes;
+// Conditions
+use pipelight_utils::expression::Expression;
 
 // Error Handling
 use log::warn;
@@ -10,7 +18,17 @@ impl Config {
      * Enforces pipeline definition rules:
      */
     pub fn strict_check(&mut self) -> Result<Config, PipelightError> {
-        self.strict_whitespace()
+        self.strict_whitespace()?;
+        self.strict_jobs()?;
+        self.strict_secrets()?;
+        self.strict_needs()?;
+        self.strict_pipelines()?;
+        self.strict_concurrency()?;
+        self.strict_timeouts()?;
+        self.strict_retries()?;
+        self.strict_limits()?;
+        self.strict_conditions()?;
+        self.strict_matrices()
     }
     /**
      * No whitespaces allowed in pipeline names.
@@ -39,4 +57,671 @@ impl Config {
         }
         Ok(self.to_owned())
     }
+    /**
+     * Step dependencies must reference existing steps of the same pipeline
+     * and must not loop.
+     */
+    pub fn strict_needs(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_needs()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Steps run either commands or an existing pipeline,
+     * and pipelines must not invoke themselves, even indirectly.
+     */
+    pub fn strict_pipelines(&mut self) -> Result<Config, PipelightError> {
+        let pipelines = self.pipelines.clone().unwrap_or_default();
+        let mut names: Vec<String> = pipelines.iter().map(|e| e.name.clone()).collect();
+        names.sort();
+
+        // Map pipeline names to the pipelines their steps invoke
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        for pipeline in &pipelines {
+            let mut invoked: Vec<String> = vec![];
+            for step in pipeline.get_steps() {
+                step.strict_pipeline(&pipeline.name, &names)?;
+                if let Some(name) = &step.pipeline {
+                    invoked.push(name.clone());
+                }
+            }
+            graph.insert(pipeline.name.clone(), invoked);
+        }
+        // Cycles
+        let mut visited: Vec<String> = vec![];
+        for name in &names {
+            let mut path: Vec<String> = vec![];
+            if let Some(cycle) = find_cycle(&graph, name, &mut path, &mut visited) {
+                let e = LibError {
+                    message: format!("The pipeline \"{}\" invokes itself.", cycle[0]),
+                    help: format!("Break the invocation cycle:\n\n{}\n", cycle.join(" -> ")),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Concurrency policies must be known ones.
+     */
+    pub fn strict_concurrency(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_concurrency()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Timeouts must be valid human readable durations (ex: "10m").
+     */
+    pub fn strict_timeouts(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_timeouts()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * The project-wide jobs limit must let at least one step run.
+     */
+    pub fn strict_jobs(&mut self) -> Result<Config, PipelightError> {
+        if let Some(options) = &self.options {
+            if options.max_jobs == Some(0) {
+                let e = LibError {
+                    message: "Invalid max_jobs in config options.".to_owned(),
+                    help: "At least one step must be able to run, ex: max_jobs = 4.".to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Secrets are read from exactly one source.
+     */
+    pub fn strict_secrets(&mut self) -> Result<Config, PipelightError> {
+        for (name, secret) in self.secrets.iter().flatten() {
+            if secret.env.is_some() == secret.file.is_some() {
+                let e = LibError {
+                    message: format!("Invalid source for secret \"{}\".", name),
+                    help: "Read the secret either from an environment variable or from a file, ex: { env = \"DEPLOY_TOKEN\" }."
+                        .to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Retry policies must run at least once,
+     * with a valid delay and a known backoff.
+     */
+    pub fn strict_retries(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_retries()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Resource limits must be parsable sizes and durations.
+     */
+    pub fn strict_limits(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_limits()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Step conditions must be valid expressions.
+     */
+    pub fn strict_conditions(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_conditions()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+    /**
+     * Matrices must have values to combine
+     * and parallelism must be at least 1.
+     */
+    pub fn strict_matrices(&mut self) -> Result<Config, PipelightError> {
+        if let Some(pipelines) = self.pipelines.clone() {
+            for pipeline in pipelines {
+                pipeline.strict_matrices()?;
+            }
+        }
+        Ok(self.to_owned())
+    }
+}
+
+/**
+ * Ensure a matrix, if any, has at least one variable
+ * and that every variable has at least one value.
+ */
+fn check_matrix(matrix: &Option<Matrix>, location: &str) -> Result<(), PipelightError> {
+    if let Some(matrix) = matrix {
+        if matrix.is_empty() || matrix.values().any(|e| e.is_empty()) {
+            let e = LibError {
+                message: format!("Empty matrix for {}.", location),
+                help: "Give every matrix variable a list of values, ex: toolchain = [\"stable\", \"nightly\"]."
+                    .to_owned(),
+            };
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+/**
+ * Ensure a max_parallel, if any, lets at least one step run.
+ */
+fn check_max_parallel(max_parallel: &Option<usize>, location: &str) -> Result<(), PipelightError> {
+    if *max_parallel == Some(0) {
+        let e = LibError {
+            message: format!("Invalid max_parallel for {}.", location),
+            help: "At least one step must be able to run, ex: max_parallel = 2.".to_owned(),
+        };
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/**
+ * Ensure a timeout, if any, can be parsed into a duration.
+ */
+fn check_timeout(timeout: &Option<String>, location: &str) -> Result<(), PipelightError> {
+    if let Some(timeout) = timeout {
+        if human_duration_to_std_duration(timeout).is_err() {
+            let e = LibError {
+                message: format!("Invalid timeout \"{}\" for {}.", timeout, location),
+                help: "Use a number followed by a unit (d, h, m, s, ms), ex: \"10m\" or \"1h30m\"."
+                    .to_owned(),
+            };
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+impl Fallback {
+    fn get_parallels(&self) -> Vec<&Parallel> {
+        [
+            &self.on_started,
+            &self.on_failure,
+            &self.on_success,
+            &self.on_abortion,
+            &self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .flat_map(|e| e.get_parallels())
+        .collect()
+    }
+    fn get_steps(&self) -> Vec<&Step> {
+        [
+            &self.on_started,
+            &self.on_failure,
+            &self.on_success,
+            &self.on_abortion,
+            &self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .flat_map(|e| e.get_steps())
+        .collect()
+    }
+    fn strict_timeouts(&self, pipeline: &str) -> Result<(), PipelightError> {
+        for steps in [
+            &self.on_started,
+            &self.on_failure,
+            &self.on_success,
+            &self.on_abortion,
+            &self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for step in steps {
+                step.strict_timeouts(pipeline)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StepOrParallel {
+    /**
+     * Every parallel block, fallbacks included.
+     */
+    fn get_parallels(&self) -> Vec<&Parallel> {
+        let mut parallels: Vec<&Parallel> = vec![];
+        let steps: Vec<&Step> = match self {
+            StepOrParallel::Step(step) => vec![step],
+            StepOrParallel::Parallel(parallel) => {
+                parallels.push(parallel);
+                if let Some(fallback) = &parallel.fallback {
+                    parallels.extend(fallback.get_parallels());
+                }
+                parallel.parallel.iter().collect()
+            }
+        };
+        for step in steps {
+            if let Some(fallback) = &step.fallback {
+                parallels.extend(fallback.get_parallels());
+            }
+        }
+        parallels
+    }
+    /**
+     * Every step, fallbacks included.
+     */
+    fn get_steps(&self) -> Vec<&Step> {
+        match self {
+            StepOrParallel::Step(step) => step.get_steps(),
+            StepOrParallel::Parallel(parallel) => {
+                let mut steps: Vec<&Step> = parallel
+                    .parallel
+                    .iter()
+                    .flat_map(|e| e.get_steps())
+                    .collect();
+                if let Some(fallback) = &parallel.fallback {
+                    steps.extend(fallback.get_steps());
+                }
+                steps
+            }
+        }
+    }
+    fn strict_timeouts(&self, pipeline: &str) -> Result<(), PipelightError> {
+        match self {
+            StepOrParallel::Step(step) => step.strict_timeouts(pipeline),
+            StepOrParallel::Parallel(parallel) => {
+                check_timeout(
+                    &parallel.timeout,
+                    &format!("a parallel block of pipeline \"{}\"", pipeline),
+                )?;
+                for step in &parallel.parallel {
+                    step.strict_timeouts(pipeline)?;
+                }
+                if let Some(fallback) = &parallel.fallback {
+                    fallback.strict_timeouts(pipeline)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Step {
+    /**
+     * The step itself and its fallbacks steps.
+     */
+    fn get_steps(&self) -> Vec<&Step> {
+        let mut steps = vec![self];
+        if let Some(fallback) = &self.fallback {
+            steps.extend(fallback.get_steps());
+        }
+        steps
+    }
+    fn strict_matrix(&self, pipeline: &str) -> Result<(), PipelightError> {
+        let location = format!("step \"{}\" of pipeline \"{}\"", self.name, pipeline);
+        check_matrix(&self.matrix, &location)?;
+        check_max_parallel(&self.max_parallel, &location)?;
+        if self.matrix.is_none() && (self.max_parallel.is_some() || self.fail_fast.is_some()) {
+            let e = LibError {
+                message: format!("Parallelism options without a matrix for {}.", location),
+                help: "Only matrix steps accept \"max_parallel\" and \"fail_fast\".".to_owned(),
+            };
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    fn strict_pipeline(&self, pipeline: &str, names: &[String]) -> Result<(), PipelightError> {
+        let location = format!("step \"{}\" of pipeline \"{}\"", self.name, pipeline);
+        match &self.pipeline {
+            Some(name) => {
+                if !self.commands.is_empty() {
+                    let e = LibError {
+                        message: format!("Both commands and a pipeline for {}.", location),
+                        help: "A step runs either commands or a pipeline, not both.".to_owned(),
+                    };
+                    return Err(e.into());
+                }
+                if !names.contains(name) {
+                    let mut help = "Available pipelines are:\n\n".to_owned();
+                    for name in names {
+                        help += &format!("{}\n", name);
+                    }
+                    let e = LibError {
+                        message: format!("Unknown pipeline \"{}\" for {}.", name, location),
+                        help,
+                    };
+                    return Err(e.into());
+                }
+            }
+            None => {
+                if self.commands.is_empty() {
+                    let e = LibError {
+                        message: format!("No commands for {}.", location),
+                        help: "Give the step commands, or a pipeline to run, ex: pipeline = \"build\"."
+                            .to_owned(),
+                    };
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+    fn strict_condition(&self, pipeline: &str) -> Result<(), PipelightError> {
+        if let Some(condition) = &self.condition {
+            if let Err(err) = condition.parse::<Expression>() {
+                let e = LibError {
+                    message: format!(
+                        "Invalid condition for step \"{}\" of pipeline \"{}\".",
+                        self.name, pipeline
+                    ),
+                    help: format!(
+                        "{}\n\nConditions compare variables (branch, tag, commit, action, env.<NAME>, steps.<name>.status)\nto quoted strings with ==, !=, &&, || and !.",
+                        err
+                    ),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+    fn strict_limits(&self, pipeline: &str) -> Result<(), PipelightError> {
+        let limits = match self.options.as_ref().and_then(|e| e.limits.as_ref()) {
+            Some(limits) => limits,
+            None => return Ok(()),
+        };
+        let location = format!("step \"{}\" of pipeline \"{}\"", self.name, pipeline);
+        if let Some(memory) = &limits.memory {
+            if human_size_to_bytes(memory).is_err() {
+                let e = LibError {
+                    message: format!("Invalid memory limit \"{}\" for {}.", memory, location),
+                    help: "Use a number followed by a unit (K, M, G, T), ex: \"512M\" or \"2G\"."
+                        .to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        if let Some(cpu_time) = &limits.cpu_time {
+            if human_duration_to_std_duration(cpu_time).is_err() {
+                let e = LibError {
+                    message: format!("Invalid cpu time limit \"{}\" for {}.", cpu_time, location),
+                    help: "Use a number followed by a unit (d, h, m, s, ms), ex: \"30m\"."
+                        .to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+    fn strict_retry(&self, pipeline: &str) -> Result<(), PipelightError> {
+        let retry = match self.options.as_ref().and_then(|e| e.retry.as_ref()) {
+            Some(retry) => retry,
+            None => return Ok(()),
+        };
+        let location = format!("step \"{}\" of pipeline \"{}\"", self.name, pipeline);
+        if retry.attempts == 0 {
+            let e = LibError {
+                message: format!("Invalid retry attempts for {}.", location),
+                help: "A step must be attempted at least once, ex: \"attempts = 3\".".to_owned(),
+            };
+            return Err(e.into());
+        }
+        if let Some(delay) = &retry.delay {
+            if human_duration_to_std_duration(delay).is_err() {
+                let e = LibError {
+                    message: format!("Invalid retry delay \"{}\" for {}.", delay, location),
+                    help: "Use a number followed by a unit (d, h, m, s, ms), ex: \"5s\"."
+                        .to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        if let Some(backoff) = &retry.backoff {
+            if !["constant", "linear", "exponential"].contains(&backoff.as_str()) {
+                let e = LibError {
+                    message: format!("Unknown retry backoff \"{}\" for {}.", backoff, location),
+                    help: "Available backoffs are: constant, linear, exponential.".to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+    fn strict_timeouts(&self, pipeline: &str) -> Result<(), PipelightError> {
+        check_timeout(
+            &self.timeout,
+            &format!("step \"{}\" of pipeline \"{}\"", self.name, pipeline),
+        )?;
+        if let Some(fallback) = &self.fallback {
+            fallback.strict_timeouts(pipeline)?;
+        }
+        Ok(())
+    }
+}
+
+impl Pipeline {
+    /**
+     * Check the matrices of every step and parallel block, fallbacks included.
+     */
+    pub fn strict_matrices(&self) -> Result<(), PipelightError> {
+        for step in self.get_steps() {
+            step.strict_matrix(&self.name)?;
+        }
+        for parallel in self.get_parallels() {
+            let location = format!("a parallel block of pipeline \"{}\"", self.name);
+            check_matrix(&parallel.matrix, &location)?;
+            check_max_parallel(&parallel.max_parallel, &location)?;
+        }
+        Ok(())
+    }
+    /**
+     * Every parallel block of the pipeline, fallbacks included.
+     */
+    fn get_parallels(&self) -> Vec<&Parallel> {
+        let mut parallels: Vec<&Parallel> =
+            self.steps.iter().flat_map(|e| e.get_parallels()).collect();
+        if let Some(fallback) = &self.fallback {
+            parallels.extend(fallback.get_parallels());
+        }
+        parallels
+    }
+    /**
+     * Check the conditions of every step, fallbacks included.
+     */
+    pub fn strict_conditions(&self) -> Result<(), PipelightError> {
+        for step in self.get_steps() {
+            step.strict_condition(&self.name)?;
+        }
+        Ok(())
+    }
+    /**
+     * Every step of the pipeline, fallbacks included.
+     */
+    fn get_steps(&self) -> Vec<&Step> {
+        let mut steps: Vec<&Step> = self.steps.iter().flat_map(|e| e.get_steps()).collect();
+        if let Some(fallback) = &self.fallback {
+            steps.extend(fallback.get_steps());
+        }
+        steps
+    }
+    /**
+     * Check the retry policies of every step, fallbacks included.
+     */
+    pub fn strict_retries(&self) -> Result<(), PipelightError> {
+        for step in self.get_steps() {
+            step.strict_retry(&self.name)?;
+        }
+        Ok(())
+    }
+    /**
+     * Check the resource limits of every step, fallbacks included.
+     */
+    pub fn strict_limits(&self) -> Result<(), PipelightError> {
+        for step in self.get_steps() {
+            step.strict_limits(&self.name)?;
+        }
+        Ok(())
+    }
+    /**
+     * Check the policy applied to homologous running instances.
+     */
+    pub fn strict_concurrency(&self) -> Result<(), PipelightError> {
+        let concurrency = match self.options.as_ref().and_then(|e| e.concurrency.as_ref()) {
+            Some(concurrency) => concurrency,
+            None => return Ok(()),
+        };
+        if !["skip", "queue", "cancel-previous", "allow"].contains(&concurrency.as_str()) {
+            let e = LibError {
+                message: format!(
+                    "Unknown concurrency \"{}\" for pipeline \"{}\".",
+                    concurrency, self.name
+                ),
+                help: "Available policies are: skip, queue, cancel-previous, allow.".to_owned(),
+            };
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    /**
+     * Check the pipeline, steps and parallel blocks timeouts.
+     */
+    pub fn strict_timeouts(&self) -> Result<(), PipelightError> {
+        check_timeout(&self.timeout, &format!("pipeline \"{}\"", self.name))?;
+        for step in &self.steps {
+            step.strict_timeouts(&self.name)?;
+        }
+        if let Some(fallback) = &self.fallback {
+            fallback.strict_timeouts(&self.name)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Check the pipeline step dependency graph.
+     * - only top level steps can declare dependencies,
+     *   parallel and fallback steps can't,
+     * - top level step names must be unique,
+     * - dependencies must be names of top level steps,
+     * - the graph must be acyclic.
+     */
+    pub fn strict_needs(&self) -> Result<(), PipelightError> {
+        let top: Vec<&Step> = self
+            .steps
+            .iter()
+            .filter_map(|e| match e {
+                StepOrParallel::Step(step) => Some(step.as_ref()),
+                StepOrParallel::Parallel(_) => None,
+            })
+            .collect();
+        for step in self.get_steps() {
+            if step.needs.is_some() && !top.iter().any(|e| ptr::eq(*e, step)) {
+                let e = LibError {
+                    message: format!(
+                        "The step \"{}\" of pipeline \"{}\" can't declare dependencies.",
+                        step.name, self.name
+                    ),
+                    help: "Move the \"needs\" field from the parallel or fallback step to a top level step."
+                        .to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        // Map step names to their dependencies
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        for step in top {
+            let needs = step.needs.clone().unwrap_or_default();
+            if graph.insert(step.name.clone(), needs).is_some() {
+                let e = LibError {
+                    message: format!(
+                        "Several steps of pipeline \"{}\" are named \"{}\".",
+                        self.name, step.name
+                    ),
+                    help: "Give every top level step a unique name, so that dependencies are unambiguous."
+                        .to_owned(),
+                };
+                return Err(e.into());
+            }
+        }
+        // Unknown dependencies
+        let mut names: Vec<String> = graph.keys().cloned().collect();
+        names.sort();
+        for name in &names {
+            for need in &graph[name] {
+                if !graph.contains_key(need) {
+                    let mut help = "Available steps are:\n\n".to_owned();
+                    for name in &names {
+                        help += &format!("{}\n", name);
+                    }
+                    let e = LibError {
+                        message: format!(
+                            "The step \"{}\" of pipeline \"{}\" needs an unknown step \"{}\".",
+                            name, self.name, need
+                        ),
+                        help,
+                    };
+                    return Err(e.into());
+                }
+            }
+        }
+        // Cycles
+        let mut visited: Vec<String> = vec![];
+        for name in &names {
+            let mut path: Vec<String> = vec![];
+            if let Some(cycle) = find_cycle(&graph, name, &mut path, &mut visited) {
+                let e = LibError {
+                    message: format!(
+                        "The steps of pipeline \"{}\" have circular dependencies.",
+                        self.name
+                    ),
+                    help: format!("Break the dependency cycle:\n\n{}\n", cycle.join(" -> ")),
+                };
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Depth first search that returns the first dependency cycle found.
+ */
+fn find_cycle(
+    graph: &HashMap<String, Vec<String>>,
+    name: &str,
+    path: &mut Vec<String>,
+    visited: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    if let Some(position) = path.iter().position(|e| e == name) {
+        let mut cycle = path[position..].to_vec();
+        cycle.push(name.to_owned());
+        return Some(cycle);
+    }
+    if visited.contains(&name.to_owned()) {
+        return None;
+    }
+    path.push(name.to_owned());
+    for need in graph.get(name).cloned().unwrap_or_default() {
+        if let Some(cycle) = find_cycle(graph, &need, path, visited) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    visited.push(name.to_owned());
+    None
 }
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::{Pipeline, Trigger, TriggerBranch, TriggerTag};
+    use crate::config::load::env::parse_dotenv;
+    use crate::{Config, Pipeline, StepOrParallel, Trigger, TriggerBranch, TriggerTag};
     use miette::Result;
+    use std::path::Path;
 
     #[test]
     fn normal() {
@@ -121,4 +123,452 @@ mod tests {
         assert!(res.is_err());
         Ok(())
     }
+    #[test]
+    fn needs() -> Result<()> {
+        let json = r#"
+          {
+            "name": "graph",
+            "steps":[
+              { "name": "lint", "commands": ["ls"] },
+              { "name": "build", "commands": ["ls"] },
+              { "name": "test", "commands": ["ls"], "needs": ["build"] },
+              { "name": "package", "commands": ["ls"], "needs": ["lint", "test"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_needs().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn needs_unknown_step() -> Result<()> {
+        let json = r#"
+          {
+            "name": "graph",
+            "steps":[
+              { "name": "test", "commands": ["ls"], "needs": ["build"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_needs().is_err());
+        Ok(())
+    }
+    #[test]
+    fn needs_cycle() -> Result<()> {
+        let json = r#"
+          {
+            "name": "graph",
+            "steps":[
+              { "name": "build", "commands": ["ls"], "needs": ["package"] },
+              { "name": "test", "commands": ["ls"], "needs": ["build"] },
+              { "name": "package", "commands": ["ls"], "needs": ["test"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_needs().is_err());
+        Ok(())
+    }
+    #[test]
+    fn needs_duplicate_name() -> Result<()> {
+        let json = r#"
+          {
+            "name": "graph",
+            "steps":[
+              { "name": "build", "commands": ["ls"] },
+              { "name": "build", "commands": ["ls"] },
+              { "name": "test", "commands": ["ls"], "needs": ["build"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_needs().is_err());
+        Ok(())
+    }
+    #[test]
+    fn needs_in_fallback() -> Result<()> {
+        let json = r#"
+          {
+            "name": "graph",
+            "steps":[
+              { "name": "build", "commands": ["ls"] }
+            ],
+            "on_failure":[
+              { "name": "notify", "commands": ["ls"], "needs": ["unknown"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_needs().is_err());
+        Ok(())
+    }
+    #[test]
+    fn timeouts() -> Result<()> {
+        let json = r#"
+          {
+            "name": "timeouts",
+            "timeout": "1h",
+            "steps":[
+              { "name": "build", "commands": ["ls"], "timeout": "10m" },
+              { "parallel": [{ "name": "test", "commands": ["ls"], "timeout": "1m30s" }], "timeout": "5m" }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_timeouts().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn timeout_invalid() -> Result<()> {
+        let json = r#"
+          {
+            "name": "timeouts",
+            "steps":[
+              { "name": "build", "commands": ["ls"], "timeout": "ten minutes" }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_timeouts().is_err());
+        Ok(())
+    }
+    #[test]
+    fn retries() -> Result<()> {
+        let json = r#"
+          {
+            "name": "retries",
+            "steps":[
+              {
+                "name": "fetch",
+                "commands": ["ls"],
+                "options": { "retry": { "attempts": 3, "delay": "5s", "backoff": "exponential" } }
+              }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_retries().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn retry_unknown_backoff() -> Result<()> {
+        let json = r#"
+          {
+            "name": "retries",
+            "steps":[
+              {
+                "name": "fetch",
+                "commands": ["ls"],
+                "options": { "retry": { "attempts": 3, "backoff": "random" } }
+              }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_retries().is_err());
+        Ok(())
+    }
+    #[test]
+    fn concurrency() -> Result<()> {
+        let json = r#"
+          {
+            "name": "deploy",
+            "options": { "concurrency": "cancel-previous" },
+            "steps":[
+              { "name": "push", "commands": ["ls"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_concurrency().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn concurrency_unknown() -> Result<()> {
+        let json = r#"
+          {
+            "name": "deploy",
+            "options": { "concurrency": "parallel" },
+            "steps":[
+              { "name": "push", "commands": ["ls"] }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_concurrency().is_err());
+        Ok(())
+    }
+    #[test]
+    fn secrets() -> Result<()> {
+        let json = r#"
+          {
+            "secrets": {
+              "TOKEN": { "env": "CI_TOKEN" },
+              "KEY": { "file": "~/.secrets/key" }
+            }
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.strict_secrets().is_ok());
+        config.resolve_paths(Path::new("."))?;
+        let file = config.secrets.unwrap()["KEY"].file.clone().unwrap();
+        assert!(Path::new(&file).is_absolute());
+        Ok(())
+    }
+    #[test]
+    fn secrets_invalid_source() -> Result<()> {
+        let json = r#"
+          {
+            "secrets": {
+              "TOKEN": { "env": "CI_TOKEN", "file": "/run/secrets/token" }
+            }
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.strict_secrets().is_err());
+        Ok(())
+    }
+    #[test]
+    fn secrets_inside_project() -> Result<()> {
+        let json = r#"
+          {
+            "secrets": {
+              "TOKEN": { "file": ".secrets/token" }
+            }
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.resolve_paths(Path::new(".")).is_err());
+        Ok(())
+    }
+    #[test]
+    fn conditions() -> Result<()> {
+        let json = r#"
+          {
+            "name": "conditions",
+            "steps":[
+              { "name": "build", "commands": ["ls"] },
+              { "name": "deploy", "commands": ["ls"], "if": "branch == 'main' && steps.build.status == 'succeeded'" }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_conditions().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn condition_invalid() -> Result<()> {
+        let json = r#"
+          {
+            "name": "conditions",
+            "steps":[
+              { "name": "deploy", "commands": ["ls"], "if": "branch = 'main'" }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_conditions().is_err());
+        Ok(())
+    }
+    #[test]
+    fn matrices() -> Result<()> {
+        let json = r#"
+          {
+            "name": "matrices",
+            "steps":[
+              {
+                "name": "test",
+                "commands": ["cargo +${{ matrix.toolchain }} test"],
+                "matrix": { "toolchain": ["stable", "nightly"], "features": ["a", "b"] },
+                "max_parallel": 2,
+                "fail_fast": true
+              }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_matrices().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn matrix_empty() -> Result<()> {
+        let json = r#"
+          {
+            "name": "matrices",
+            "steps":[
+              { "name": "test", "commands": ["ls"], "matrix": { "toolchain": [] } }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_matrices().is_err());
+        Ok(())
+    }
+    #[test]
+    fn dotenv() -> Result<()> {
+        let string = r#"
+          # comment
+          export TOKEN="abc"
+          LEVEL = 'debug'
+          EMPTY=
+        "#;
+        let env = parse_dotenv(string).unwrap();
+        assert_eq!(env.get("TOKEN"), Some(&"abc".to_owned()));
+        assert_eq!(env.get("LEVEL"), Some(&"debug".to_owned()));
+        assert_eq!(env.get("EMPTY"), Some(&"".to_owned()));
+        assert!(parse_dotenv("NOT A VARIABLE").is_err());
+        Ok(())
+    }
+    #[test]
+    fn cwd() -> Result<()> {
+        let json = r#"
+          {
+            "pipelines": [{
+              "name": "cwd",
+              "steps":[
+                { "name": "list", "commands": ["ls"], "options": { "cwd": "src" } }
+              ]
+            }]
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        config.resolve_paths(Path::new("."))?;
+        let pipeline = &config.pipelines.unwrap()[0];
+        let StepOrParallel::Step(step) = &pipeline.steps[0] else {
+            panic!("expected a step");
+        };
+        let expected = Path::new("src").canonicalize().unwrap();
+        assert_eq!(
+            step.options.as_ref().unwrap().cwd,
+            Some(expected.display().to_string())
+        );
+        Ok(())
+    }
+    #[test]
+    fn cwd_missing() -> Result<()> {
+        let json = r#"
+          {
+            "pipelines": [{
+              "name": "cwd",
+              "steps":[
+                { "name": "list", "commands": ["ls"], "options": { "cwd": "not_a_dir" } }
+              ]
+            }]
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.resolve_paths(Path::new(".")).is_err());
+        Ok(())
+    }
+    #[test]
+    fn max_jobs() -> Result<()> {
+        let json = r#"
+          {
+            "options": { "max_jobs": 0 },
+            "pipelines": []
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.strict_jobs().is_err());
+        Ok(())
+    }
+    #[test]
+    fn pipelines() -> Result<()> {
+        let json = r#"
+          {
+            "pipelines": [
+              { "name": "build", "steps": [{ "name": "compile", "commands": ["ls"] }] },
+              { "name": "release", "steps": [{ "name": "build", "pipeline": "build" }] }
+            ]
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.strict_pipelines().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn pipelines_unknown() -> Result<()> {
+        let json = r#"
+          {
+            "pipelines": [
+              { "name": "release", "steps": [{ "name": "build", "pipeline": "build" }] }
+            ]
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.strict_pipelines().is_err());
+        Ok(())
+    }
+    #[test]
+    fn pipelines_cycle() -> Result<()> {
+        let json = r#"
+          {
+            "pipelines": [
+              { "name": "build", "steps": [{ "name": "deploy", "pipeline": "deploy" }] },
+              { "name": "deploy", "steps": [{ "name": "build", "pipeline": "build" }] }
+            ]
+          }
+        "#;
+        let mut config = serde_json::from_str::<Config>(&json).unwrap();
+        assert!(config.strict_pipelines().is_err());
+        Ok(())
+    }
+    #[test]
+    fn sandbox() -> Result<()> {
+        let json = r#"
+          {
+            "name": "build",
+            "steps":[
+              {
+                "name": "compile",
+                "commands": ["cargo build"],
+                "options": { "sandbox": { "network": true } }
+              }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        let json = serde_json::to_value(&pipeline).unwrap();
+        assert_eq!(json["steps"][0]["options"]["sandbox"]["network"], true);
+        Ok(())
+    }
+    #[test]
+    fn limits() -> Result<()> {
+        let json = r#"
+          {
+            "name": "test",
+            "steps":[
+              {
+                "name": "unit",
+                "commands": ["cargo test"],
+                "options": {
+                  "limits": { "memory": "2G", "cpu_time": "30m", "nofile": 4096, "nproc": 512 }
+                }
+              }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_limits().is_ok());
+        Ok(())
+    }
+    #[test]
+    fn limits_invalid_memory() -> Result<()> {
+        let json = r#"
+          {
+            "name": "test",
+            "steps":[
+              {
+                "name": "unit",
+                "commands": ["cargo test"],
+                "options": { "limits": { "memory": "2 gigs" } }
+              }
+            ]
+          }
+        "#;
+        let pipeline = serde_json::from_str::<Pipeline>(&json).unwrap();
+        assert!(pipeline.strict_limits().is_err());
+        Ok(())
+    }
 }
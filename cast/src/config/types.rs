@@ -3,6 +3,19 @@
 // to parse structs from filepaths.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/**
+A matrix maps variable names to their possible values.
+A step or a parallel block with a matrix is expanded
+into one step per combination of values.
+*/
+pub type Matrix = BTreeMap<String, Vec<String>>;
+
+/**
+Environment variables names and values.
+*/
+pub type Env = BTreeMap<String, String>;
 
 /**
 Options to tweak global pipelines behavior
@@ -14,6 +27,14 @@ pub struct ConfigOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<String>,
+    // Environment variables of every pipelines commands.
+    pub env: Option<Env>,
+    // Dotenv file path, relative to the config file directory.
+    pub env_file: Option<String>,
+    // Maximum number of steps running at the same time, per pipeline run:
+    // every run is its own process, with its own job slots.
+    // Defaults to the number of available cores.
+    pub max_jobs: Option<usize>,
 }
 
 /**
@@ -32,6 +53,21 @@ It is as is to let room for top level configuration that will come after **v1.0.
 pub struct Config {
     pub pipelines: Option<Vec<Pipeline>>,
     pub options: Option<ConfigOpts>,
+    // Secret names and where to read their values from.
+    pub secrets: Option<BTreeMap<String, Secret>>,
+}
+
+/**
+Where to read a secret value from:
+an environment variable or a file outside of the project.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Secret {
+    // Name of the environment variable that holds the value.
+    pub env: Option<String>,
+    // Absolute path (or ~/path) of the file that holds the value.
+    pub file: Option<String>,
 }
 
 /**
@@ -44,6 +80,15 @@ pub struct PipelineOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<String>,
+    // Environment variables of the pipeline commands.
+    // Override the config ones.
+    pub env: Option<Env>,
+    // Dotenv file path, relative to the config file directory.
+    pub env_file: Option<String>,
+    // What to do when an instance of the pipeline is already running:
+    // skip, queue, cancel-previous or allow.
+    // Defaults to skip.
+    pub concurrency: Option<String>,
 }
 
 /**
@@ -55,6 +100,8 @@ pub struct Pipeline {
     pub name: String,
     pub triggers: Option<Vec<Trigger>>,
     pub steps: Vec<StepOrParallel>,
+    // Maximum pipeline execution time (ex: "1h30m").
+    pub timeout: Option<String>,
     #[serde(flatten)]
     pub fallback: Option<Fallback>,
     pub options: Option<PipelineOpts>,
@@ -67,6 +114,62 @@ Options to tweak step behavior and command execution
 pub struct StepOpts {
     // The step's command execution behavior
     pub mode: Option<String>,
+    // Re-run the step commands on failure
+    pub retry: Option<Retry>,
+    // Environment variables of the step commands.
+    // Override the pipeline ones.
+    pub env: Option<Env>,
+    // Dotenv file path, relative to the config file directory.
+    pub env_file: Option<String>,
+    // Working directory of the step commands,
+    // relative to the config file directory.
+    pub cwd: Option<String>,
+    // Run the step commands isolated from the host.
+    pub sandbox: Option<Sandbox>,
+    // Bound the resources of the step commands.
+    pub limits: Option<Limits>,
+}
+
+/**
+Resource limits of every step command and its children.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Limits {
+    // Maximum memory (ex: "512M", "2G").
+    pub memory: Option<String>,
+    // Maximum CPU time (ex: "30m").
+    pub cpu_time: Option<String>,
+    // Maximum number of open files.
+    pub nofile: Option<u64>,
+    // Maximum number of processes.
+    pub nproc: Option<u64>,
+}
+
+/**
+Isolation of the step commands (Linux only):
+the project and $HOME are read-only, /tmp is a writable scratch directory.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Sandbox {
+    // Whether the commands can reach the network.
+    // Defaults to false.
+    pub network: Option<bool>,
+}
+
+/**
+Retry policy of a failing step.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Retry {
+    // Maximum number of executions, the first one included.
+    pub attempts: u32,
+    // Time to wait before the next attempt (ex: "5s").
+    pub delay: Option<String>,
+    // How the delay grows between attempts: "constant", "linear" or "exponential".
+    pub backoff: Option<String>,
 }
 
 /**
@@ -76,7 +179,28 @@ Steps are a named list of Commands.
 #[serde(deny_unknown_fields)]
 pub struct Step {
     pub name: String,
+    #[serde(default)]
     pub commands: Vec<String>,
+    // Name of a pipeline to run instead of commands.
+    pub pipeline: Option<String>,
+    // Names of the steps that must be done before this one starts.
+    pub needs: Option<Vec<String>>,
+    // Maximum step execution time (ex: "10m").
+    pub timeout: Option<String>,
+    // Condition to run the step (ex: "branch == 'main' && action == 'pre-push'").
+    #[serde(rename = "if")]
+    pub condition: Option<String>,
+    // Run the step for every combination of the matrix values.
+    pub matrix: Option<Matrix>,
+    // Maximum number of matrix combinations running at the same time.
+    pub max_parallel: Option<usize>,
+    // Stop launching matrix combinations as soon as one fails.
+    pub fail_fast: Option<bool>,
+    // Files to keep after the run (ex: ["target/release/app", "coverage/*.lcov"]).
+    pub artifacts: Option<Vec<String>>,
+    // Files the step result depends on (ex: ["src/**", "Cargo.lock"]).
+    // The step is skipped when they are unchanged since a previous success.
+    pub inputs: Option<Vec<String>>,
     pub options: Option<StepOpts>,
     #[serde(flatten)]
     pub fallback: Option<Fallback>,
@@ -91,6 +215,14 @@ pub struct Parallel {
     pub parallel: Vec<Step>,
     // pub options: Option<StepOpts>,
     pub mode: Option<String>,
+    // Maximum parallel block execution time (ex: "10m").
+    pub timeout: Option<String>,
+    // Run the block steps for every combination of the matrix values.
+    pub matrix: Option<Matrix>,
+    // Maximum number of steps running at the same time.
+    pub max_parallel: Option<usize>,
+    // Stop launching steps as soon as one fails.
+    pub fail_fast: Option<bool>,
     #[serde(flatten)]
     pub fallback: Option<Fallback>,
 }
@@ -106,8 +238,8 @@ a usable **Union** (Step must be This type OR This type).
 #[serde(untagged)]
 #[serde(deny_unknown_fields)]
 pub enum StepOrParallel {
-    Step(Step),
-    Parallel(Parallel),
+    Step(Box<Step>),
+    Parallel(Box<Parallel>),
 }
 
 /**
@@ -123,6 +255,8 @@ pub struct Fallback {
     pub on_failure: Option<Vec<StepOrParallel>>,
     pub on_success: Option<Vec<StepOrParallel>>,
     pub on_abortion: Option<Vec<StepOrParallel>>,
+    // Run after the others, whatever the outcome
+    pub on_finished: Option<Vec<StepOrParallel>>,
 }
 
 /**
@@ -0,0 +1,55 @@
+// Types
+use workflow::{Getters, Logs};
+// Filesystem
+use std::fs;
+use std::path::Path;
+// Error Handling
+use miette::{Error, IntoDiagnostic, Result};
+
+/**
+List the artifacts of a pipeline run,
+or copy them into the output directory.
+The last run is used if no run uuid is provided.
+*/
+pub fn launch(name: &str, run: Option<String>, out: Option<String>) -> Result<()> {
+    let pipeline = match run {
+        Some(uuid) => Logs::get_by_uuid(&uuid)?,
+        None => Logs::get_by_name(name)?,
+    };
+    if pipeline.name != name {
+        let message = format!(
+            "The run {} belongs to pipeline {:?}, not {:?}",
+            pipeline.uuid, pipeline.name, name
+        );
+        return Err(Error::msg(message));
+    }
+
+    let steps = pipeline.get_artifact_steps();
+    if steps.is_empty() {
+        let message = format!("The run {} has no artifacts", pipeline.uuid);
+        return Err(Error::msg(message));
+    }
+    for step in steps {
+        let dir = step.get_artifacts_dir(&pipeline.uuid);
+        let files = step.artifacts.unwrap().files.unwrap_or_default();
+        match &out {
+            Some(out) => {
+                let dest = Path::new(out).join(dir.file_name().unwrap());
+                for file in &files {
+                    let target = dest.join(file);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent).into_diagnostic()?;
+                    }
+                    fs::copy(dir.join(file), target).into_diagnostic()?;
+                }
+            }
+            None => {
+                println!("{}:", step.name);
+                for file in &files {
+                    println!("  {}", dir.join(file).display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
 // Types
+use super::prompt;
+use super::run::EXIT_CODE;
 use pipelight_exec::{Statuable, Status};
+use std::process::ExitCode;
 use workflow::{pipeline::Filters, Getters, Logs, Node, Pipeline};
 // Error Handling
 use miette::{IntoDiagnostic, Result};
@@ -40,6 +43,25 @@ pub fn json(name: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/**
+Follow the most recent running run of a pipeline until it finishes,
+and exit with the pipeline status.
+*/
+pub fn follow(name: Option<String>) -> Result<()> {
+    let name = match name {
+        Some(name) => name,
+        None => prompt::running_pipeline()?,
+    };
+    let mut pipeline = Logs::get_running_by_name(&name)?;
+    pipeline.follow()?;
+
+    *EXIT_CODE.lock().unwrap() = match pipeline.status {
+        Some(Status::Succeeded) => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    };
+    Ok(())
+}
+
 /**
 Clean
 */
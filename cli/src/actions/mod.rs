@@ -1,6 +1,8 @@
 // Test
 // Actions
+pub mod artifacts;
 pub mod logs;
+pub mod plan;
 pub mod run;
 pub mod stop;
 pub mod trigger;
@@ -0,0 +1,163 @@
+// Struct
+use crate::actions::run::link_previous_run;
+use crate::types;
+use workflow::{Event, Getters, Node, Pipeline};
+// Globals
+use log::LevelFilter;
+use pipelight_utils::globals::LOGGER;
+// Error Handling
+use miette::{IntoDiagnostic, Result};
+
+/**
+Print what `pipelight run <name>` would execute,
+without spawning any command nor writing logs.
+Steps reused from a previous run (--from, --resume) are marked as such.
+*/
+pub fn run(name: &str, args: &types::Pipeline) -> Result<()> {
+    let mut pipeline = Pipeline::get_by_name(name)?;
+    link_previous_run(&mut pipeline, args)?;
+    // Guard
+    pipeline.is_triggerable()?;
+    print(vec![pipeline], args.trigger.json)
+}
+
+/**
+Print what `pipelight trigger` would execute:
+the plans of the pipelines that match the triggering environment.
+*/
+pub fn trigger(json: bool) -> Result<()> {
+    let pipelines: Vec<Pipeline> = Pipeline::get()?
+        .into_iter()
+        .filter(|e| e.is_triggerable_strict().is_ok())
+        .collect();
+    if pipelines.is_empty() && !json {
+        println!("No pipeline would be triggered.");
+        return Ok(());
+    }
+    print(pipelines, json)
+}
+
+/**
+Print the pipelines plans as a tree or as json.
+*/
+fn print(pipelines: Vec<Pipeline>, json: bool) -> Result<()> {
+    let plans = plan(pipelines);
+    if json {
+        let json = serde_json::to_string_pretty(&plans).into_diagnostic()?;
+        println!("{}", json);
+    } else {
+        // Display every step and command
+        LOGGER.lock().unwrap().pipelines.level = LevelFilter::max();
+        for pipeline in &plans {
+            print!("{}", Node::from(pipeline));
+        }
+    }
+    Ok(())
+}
+
+/**
+The pipelines as they would be run,
+with the resolved triggering environment.
+*/
+fn plan(pipelines: Vec<Pipeline>) -> Vec<Pipeline> {
+    let mut plans: Vec<Pipeline> = vec![];
+    for mut pipeline in pipelines {
+        // The triggering environment, not tied to this process
+        pipeline.event = Some(Event {
+            pid: None,
+            pgid: None,
+            sid: None,
+            ..Event::default()
+        });
+        plans.push(pipeline);
+    }
+    plans
+}
+
+#[cfg(test)]
+mod test {
+    use super::plan;
+    use crate::actions::run::link_previous_run;
+    use crate::types;
+    use miette::Result;
+    use std::path::Path;
+    use workflow::{Command, Logs, Node, Pipeline, Step, StepOrParallel};
+
+    fn pipeline(name: &str, deploy: &str) -> Pipeline {
+        Pipeline {
+            name: name.to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new("echo build")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "deploy".to_owned(),
+                    commands: vec![Command::new(deploy)],
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        }
+    }
+    /**
+    Every value of the tree, depth first.
+    */
+    fn values(node: &Node) -> Vec<String> {
+        let mut all: Vec<String> = node.value.clone().into_iter().collect();
+        for child in node.children.iter().flatten() {
+            all.extend(values(child));
+        }
+        all
+    }
+
+    #[test]
+    fn plan_tree_and_json() -> Result<()> {
+        let witness = ".pipelight/test_plan_spawned";
+        let p = pipeline("test_plan", &format!("touch {}", witness));
+        let plans = plan(vec![p.clone()]);
+
+        // Tree
+        let tree = values(&Node::from(&plans[0])).join("\n");
+        assert!(tree.contains("pipeline: test_plan"));
+        assert!(tree.contains("step: deploy"));
+        assert!(tree.contains(&format!("touch {}", witness)));
+
+        // Json
+        let json = serde_json::to_string_pretty(&plans).unwrap();
+        let plans: Vec<Pipeline> = serde_json::from_str(&json).unwrap();
+        assert_eq!(plans[0].name, "test_plan");
+        assert_eq!(plans[0].steps.len(), 2);
+        assert_eq!(plans[0].event.as_ref().unwrap().pid, None);
+
+        // Nothing has been run nor logged
+        assert!(!Path::new(witness).exists());
+        Logs::refresh();
+        assert!(Logs::get_by_uuid(&p.uuid.to_string()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn plan_resume() -> Result<()> {
+        let mut previous = pipeline("test_plan_resume", "false");
+        previous.run()?;
+        Logs::refresh();
+
+        let mut p = pipeline("test_plan_resume", "echo deploy");
+        let args = types::Pipeline {
+            name: Some(p.name.clone()),
+            from: Some("deploy".to_owned()),
+            ..Default::default()
+        };
+        link_previous_run(&mut p, &args)?;
+        let plans = plan(vec![p.clone()]);
+
+        let tree = values(&Node::from(&plans[0])).join("\n");
+        assert!(tree.contains(&format!("resumes {}", previous.uuid)));
+        assert!(tree.contains("step: build (reused)"));
+        Logs::refresh();
+        assert!(Logs::get_by_uuid(&p.uuid.to_string()).is_err());
+        Ok(())
+    }
+}
@@ -2,6 +2,8 @@
 use crate::services::{Action, FgBg, Service};
 use crate::types::{Attach, Commands, DetachableCommands, PostCommands};
 use pipelight_exec::Status;
+use workflow::globals::LIVE_OUTPUT;
+use workflow::scheduler::JOBS;
 use workflow::{Config, Getters, Node, Pipeline};
 // Globals
 use crate::globals::CLI;
@@ -10,7 +12,7 @@ use log::LevelFilter;
 use pipelight_utils::globals::LOGGER;
 
 // Error Handling
-use super::EXIT_CODE;
+use super::{link_previous_run, EXIT_CODE};
 use miette::{Error, Result};
 use std::process::{ExitCode, Termination};
 
@@ -19,9 +21,11 @@ pub fn launch() -> Result<()> {
 
     // Retrieve command line args
     let name: String;
+    let run_args;
     match args.commands.clone() {
         Commands::PostCommands(PostCommands::DetachableCommands(DetachableCommands::Run(e))) => {
-            name = e.name.unwrap();
+            name = e.name.clone().unwrap();
+            run_args = e;
         }
         _ => {
             let message = "Couldn't retrieve pipeline name";
@@ -31,9 +35,12 @@ pub fn launch() -> Result<()> {
 
     let mut pipeline = Pipeline::get_by_name(&name)?;
     let config = Config::get()?;
+    link_previous_run(&mut pipeline, &run_args)?;
 
     // Guard
     pipeline.is_triggerable()?;
+    // Bound the number of steps running at the same time
+    JOBS.set_limit(config.get_max_jobs());
     if args.verbose.log_level_filter() == LevelFilter::Error {
         // Retrieve global options
         if config.has_loglevel_option().unwrap() {
@@ -77,6 +84,8 @@ pub fn launch() -> Result<()> {
     match args.attach.clone() {
         Some(val) => {
             if val == String::from(&Attach::True) {
+                // Show the commands output as it arrives
+                *LIVE_OUTPUT.lock().unwrap() = true;
                 pipeline.run()?;
                 // Return pipeline log
                 println!("{}", Node::from(&pipeline));
@@ -96,6 +105,11 @@ pub fn launch() -> Result<()> {
                         let message = "Pipeline status: Aborted";
                         Err(Error::msg(message))
                     }
+                    Some(Status::TimedOut) => {
+                        *EXIT_CODE.lock().unwrap() = ExitCode::FAILURE;
+                        let message = "Pipeline status: Timed out";
+                        Err(Error::msg(message))
+                    }
                     _ => Ok(()),
                 };
             }
@@ -1,6 +1,12 @@
 pub mod loose;
 pub mod strict;
 
+// Struct
+use crate::types;
+use workflow::{Getters, Logs, Pipeline};
+// Error Handling
+use miette::Result;
+
 // Global vars
 use once_cell::sync::Lazy;
 use std::process::ExitCode;
@@ -8,3 +14,22 @@ use std::sync::{Arc, Mutex};
 
 pub static EXIT_CODE: Lazy<Arc<Mutex<ExitCode>>> =
     Lazy::new(|| Arc::new(Mutex::new(ExitCode::default())));
+
+/**
+Repeat or resume a previous run of the pipeline,
+according to the command line arguments.
+Resuming without a run uuid resumes the last run.
+*/
+pub fn link_previous_run(pipeline: &mut Pipeline, args: &types::Pipeline) -> Result<()> {
+    if let Some(uuid) = &args.rerun {
+        pipeline.rerun(&Logs::get_by_uuid(uuid)?)?;
+    }
+    if args.resume.is_some() || args.from.is_some() {
+        let previous = match &args.resume {
+            Some(uuid) => Logs::get_by_uuid(uuid)?,
+            None => Logs::get_by_name(&pipeline.name)?,
+        };
+        pipeline.resume(&previous, args.from.as_deref())?;
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 // Struct
 use crate::types::{Commands, DetachableCommands, PostCommands};
 use pipelight_exec::Status;
+use workflow::scheduler::JOBS;
 use workflow::{Getters, Node, Pipeline};
 // Globals
 use crate::globals::CLI;
@@ -9,6 +10,7 @@ use log::LevelFilter;
 use pipelight_utils::globals::LOGGER;
 
 // Error Handling
+use super::link_previous_run;
 use miette::{Error, Result};
 
 /*
@@ -23,9 +25,11 @@ pub fn launch() -> Result<()> {
 
     // Retrieve command line args
     let name: String;
-    match args.commands {
+    let run_args;
+    match args.commands.clone() {
         Commands::PostCommands(PostCommands::DetachableCommands(DetachableCommands::Run(e))) => {
-            name = e.name.unwrap();
+            name = e.name.clone().unwrap();
+            run_args = e;
         }
         _ => {
             let message = "Couldn.t retrieve pipeline name";
@@ -35,9 +39,12 @@ pub fn launch() -> Result<()> {
 
     let mut pipeline = Pipeline::get_by_name(&name)?;
     let config = workflow::Config::get()?;
+    link_previous_run(&mut pipeline, &run_args)?;
 
     // Guard
     pipeline.is_triggerable()?;
+    // Bound the number of steps running at the same time
+    JOBS.set_limit(config.get_max_jobs());
     if args.verbose.log_level_filter() == LevelFilter::Error {
         // Retrieve global options
         if config.has_loglevel_option().unwrap() {
@@ -6,8 +6,8 @@ use crate::types::{Attach, Commands, DetachableCommands, Pipeline, PostCommands,
 use workflow;
 // Traits
 use workflow::Getters;
-// IterMut
-use rayon::prelude::*;
+// Parallelism
+use workflow::scheduler::Scheduler;
 // Globals
 use crate::globals::CLI;
 // Error Handling
@@ -32,10 +32,12 @@ pub fn launch() -> Result<()> {
     let mut pipelines = workflow::Pipeline::get()?;
     let config = workflow::Config::get()?;
 
-    pipelines.par_iter_mut().for_each(|pipeline| {
+    // Pipeline launches are unbounded,
+    // max_jobs only bounds the steps run in parallel by each launched run.
+    Scheduler::new(None).for_each(&mut pipelines, |_, pipeline| {
         // Guard
         if pipeline.is_triggerable_strict().is_err() {
-            return;
+            return Ok(true);
         }
 
         let mut args = CLI.lock().unwrap().clone();
@@ -76,12 +78,11 @@ pub fn launch() -> Result<()> {
             DetachableCommands::Run(Pipeline {
                 trigger: trigger.to_owned(),
                 name: Some(pipeline.name.clone()),
+                ..Pipeline::default()
             }),
         ));
-        Service::new(Action::RunLoose, Some(args))
-            .unwrap()
-            .should_detach()
-            .unwrap();
-    });
+        Service::new(Action::RunLoose, Some(args))?.should_detach()?;
+        Ok(true)
+    })?;
     Ok(())
 }
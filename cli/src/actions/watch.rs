@@ -35,7 +35,10 @@ pub fn watch_and_trigger() -> Result<()> {
     let flag = Some(String::from(&Flag::Special(Special::Watch)));
     let mut args = CLI.lock().unwrap().clone();
     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
-        DetachableCommands::Trigger(Trigger { flag }),
+        DetachableCommands::Trigger(Trigger {
+            flag,
+            ..Trigger::default()
+        }),
     ));
     Service::new(services::Action::Trigger, Some(args))?.should_detach()?;
     Ok(())
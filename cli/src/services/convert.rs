@@ -21,6 +21,9 @@ impl Parser for Service {
         let mut flag = Some(String::from(&Flag::default()));
         // Run options
         let mut name = None;
+        let mut from = None;
+        let mut resume = None;
+        let mut rerun = None;
 
         // Retrieve reusable arguments and mutate the defaults
         if let Some(args) = self.args.clone() {
@@ -34,6 +37,9 @@ impl Parser for Service {
                     DetachableCommands::Run(pipeline) => {
                         flag = pipeline.trigger.flag;
                         name = pipeline.name;
+                        from = pipeline.from;
+                        resume = pipeline.resume;
+                        rerun = pipeline.rerun;
                     }
                     _ => {}
                 }
@@ -46,8 +52,14 @@ impl Parser for Service {
                 if let Some(ref mut args) = self.args {
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
                         DetachableCommands::Run(Pipeline {
-                            trigger: Trigger { flag },
+                            trigger: Trigger {
+                                flag,
+                                ..Trigger::default()
+                            },
                             name,
+                            from,
+                            resume,
+                            rerun,
                         }),
                     ));
                 }
@@ -56,8 +68,14 @@ impl Parser for Service {
                 if let Some(ref mut args) = self.args {
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
                         DetachableCommands::Run(Pipeline {
-                            trigger: Trigger { flag },
+                            trigger: Trigger {
+                                flag,
+                                ..Trigger::default()
+                            },
                             name,
+                            from,
+                            resume,
+                            rerun,
                         }),
                     ));
                 }
@@ -65,7 +83,10 @@ impl Parser for Service {
             Action::Trigger => {
                 if let Some(ref mut args) = self.args {
                     args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
-                        DetachableCommands::Trigger(Trigger { flag }),
+                        DetachableCommands::Trigger(Trigger {
+                            flag,
+                            ..Trigger::default()
+                        }),
                     ))
                 }
             }
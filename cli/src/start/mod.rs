@@ -1,11 +1,13 @@
 // Struct
-use crate::actions::{logs, prompt, stop};
+use crate::actions::run::link_previous_run;
+use crate::actions::{artifacts, logs, plan, prompt, stop};
 use crate::services::{Action, Service};
+use crate::types;
 use crate::types::Cli;
 use crate::types::{ColoredOutput, LogsCommands, ToggleCommands};
 use crate::types::{Commands, DetachableCommands, PostCommands, PreCommands};
 use pipelight_watcher::Watcher;
-use workflow::{Getters, Pipeline};
+use workflow::{Getters, Logs, Pipeline};
 
 use pipelight_utils::git::Hook;
 // Clap
@@ -86,25 +88,44 @@ impl DetachableCommands {
             DetachableCommands::Run(e) => {
                 if e.name.is_none() {
                     e.name = Some(prompt::pipeline()?);
-                    args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
-                        DetachableCommands::Run(e.to_owned()),
-                    ))
                 }
+                args.commands = Commands::PostCommands(PostCommands::DetachableCommands(
+                    DetachableCommands::Run(e.to_owned()),
+                ));
                 if let Some(name) = e.name.clone() {
+                    if e.trigger.dry_run {
+                        plan::run(&name, e)?;
+                        return Ok(());
+                    }
                     // Usefull SafeGuard that early returns fancy error
                     // if pipeline name not found
                     // on detach mode
-                    let pipeline = Pipeline::get_by_name(&name)?;
+                    let mut pipeline = Pipeline::get_by_name(&name)?;
+                    link_previous_run(&mut pipeline, e)?;
                     pipeline.is_triggerable()?;
 
                     Service::new(Action::RunLoose, Some(args))?.should_detach()?;
                 }
             }
+            DetachableCommands::Rerun(e) => {
+                let previous = Logs::get_by_uuid(&e.uuid)?;
+                let mut run = DetachableCommands::Run(types::Pipeline {
+                    name: Some(previous.name),
+                    trigger: types::Trigger::default(),
+                    rerun: Some(e.uuid.clone()),
+                    ..types::Pipeline::default()
+                });
+                run.start()?;
+            }
             DetachableCommands::Watch => {
                 Service::new(Action::Watch, Some(args))?.should_detach()?;
             }
-            DetachableCommands::Trigger(..) => {
-                Service::new(Action::Trigger, Some(args))?.should_detach()?
+            DetachableCommands::Trigger(e) => {
+                if e.dry_run {
+                    plan::trigger(e.json)?;
+                } else {
+                    Service::new(Action::Trigger, Some(args))?.should_detach()?
+                }
             }
         }
         Ok(())
@@ -140,12 +161,22 @@ impl PostCommands {
                         ColoredOutput::Auto => {}
                     }
                 }
-                if e.display.json {
+                if e.follow {
+                    logs::follow(e.display.name.clone())?;
+                } else if e.display.json {
                     logs::json(e.display.name.clone())?;
                 } else {
                     logs::pretty(e.display.name.clone())?;
                 }
             }
+            PostCommands::Artifacts(e) => {
+                let name = match e.name.clone() {
+                    Some(name) => name,
+                    // Select prompt
+                    None => prompt::pipeline()?,
+                };
+                artifacts::launch(&name, e.run.clone(), e.out.clone())?;
+            }
             PostCommands::Ls(e) => {
                 if e.name.is_some() {
                     if e.json {
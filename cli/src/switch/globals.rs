@@ -110,6 +110,7 @@ pub fn hydrate_config() -> Result<()> {
 
     let casted_config = cast::Config::load(&portal.target.file_path.unwrap(), args.raw.clone())?;
     let config = Config::from(&casted_config);
+    config.load_secrets();
     *CONFIG.lock().unwrap() = config.clone();
 
     Ok(())
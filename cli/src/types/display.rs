@@ -1,7 +1,7 @@
 // Structs
 use crate::types::{
-    Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, Pipeline, Shell, Toggle,
-    ToggleCommands, Trigger,
+    Artifacts, Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, Pipeline, Rerun, Shell,
+    Toggle, ToggleCommands, Trigger,
 };
 use crate::types::{Commands, DetachableCommands, PostCommands, PreCommands};
 use crate::types::{InternalVerbosity, Verbosity};
@@ -53,10 +53,12 @@ impl fmt::Display for Commands {
                 PostCommands::DetachableCommands(detachable_command) => match detachable_command {
                     DetachableCommands::Run(pipeline) => format!("run{}", pipeline),
                     DetachableCommands::Trigger(trigger) => format!("trigger{}", trigger),
+                    DetachableCommands::Rerun(rerun) => format!("rerun{}", rerun),
                     DetachableCommands::Watch => "watch".to_owned(),
                 },
                 PostCommands::Stop(pipeline) => format!("stop{}", pipeline),
                 PostCommands::Logs(logs) => format!("logs{}", logs),
+                PostCommands::Artifacts(artifacts) => format!("artifacts{}", artifacts),
                 PostCommands::Inspect(pipeline) => format!("inspect{}", pipeline),
                 PostCommands::Ls(list) => format!("ls{}", list),
             },
@@ -81,11 +83,15 @@ impl fmt::Display for Pipeline {
             string += " ";
             string += &escape(&self.name.clone().unwrap());
         }
-        if self.trigger.flag.is_some() {
-            string += " ";
-            string += "--flag";
-            string += " ";
-            string += &self.trigger.flag.clone().unwrap();
+        string += &format!("{}", self.trigger);
+        if let Some(from) = &self.from {
+            string += &format!(" --from {:?}", from);
+        }
+        if let Some(resume) = &self.resume {
+            string += &format!(" --resume {}", resume);
+        }
+        if let Some(rerun) = &self.rerun {
+            string += &format!(" --rerun {}", rerun);
         }
         write!(f, "{}", string)
     }
@@ -128,12 +134,40 @@ impl fmt::Display for Logs {
                     string += "rm";
                 }
             }
+        }
+        if self.follow {
+            string += " --follow";
+        }
+        if self.commands.is_some() || self.follow {
             string += &format!("{}", &self.display);
         }
         write!(f, "{}", string)
     }
 }
 
+impl fmt::Display for Artifacts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut string = "".to_owned();
+        if let Some(name) = &self.name {
+            string += " ";
+            string += name;
+        }
+        if let Some(run) = &self.run {
+            string += &format!(" --run {}", run);
+        }
+        if let Some(out) = &self.out {
+            string += &format!(" --out {}", out);
+        }
+        write!(f, "{}", string)
+    }
+}
+
+impl fmt::Display for Rerun {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, " {}", self.uuid)
+    }
+}
+
 impl fmt::Display for Toggle {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut string = "".to_owned();
@@ -162,6 +196,14 @@ impl fmt::Display for Trigger {
             string += " ";
             string += &self.flag.clone().unwrap();
         }
+        if self.dry_run {
+            string += " ";
+            string += "--dry-run";
+        }
+        if self.json {
+            string += " ";
+            string += "--json";
+        }
         write!(f, "{}", string)
     }
 }
@@ -195,7 +237,8 @@ fn from_verbosity_to_string(e: Verbosity) -> String {
 mod display {
     // Structs
     use crate::types::{
-        Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, Pipeline, Shell, Toggle, Trigger,
+        Artifacts, Attach, Cli, DisplayCommands, Init, Logs, LogsCommands, Pipeline, Shell, Toggle,
+        Trigger,
     };
     use crate::types::{Commands, DetachableCommands, PostCommands, PreCommands};
     use crate::types::{InternalVerbosity, Verbosity};
@@ -210,7 +253,9 @@ mod display {
                     name: Some("test".to_owned()),
                     trigger: Trigger {
                         flag: Some("pre-push".to_owned()),
+                        ..Trigger::default()
                     },
+                    ..Pipeline::default()
                 }),
             )),
             attach: Some(String::from(&Attach::False)),
@@ -225,11 +270,31 @@ mod display {
         assert_eq!(result, "run \"test\" --flag pre-push");
     }
     #[test]
+    fn dry_run_args() {
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::DetachableCommands(
+                DetachableCommands::Trigger(Trigger {
+                    dry_run: true,
+                    json: true,
+                    ..Trigger::default()
+                }),
+            )),
+            attach: None,
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        assert_eq!(result, "trigger --dry-run --json");
+    }
+    #[test]
     fn logs_args() {
         // Define a cli struct
         let cli = Cli {
             commands: Commands::PostCommands(PostCommands::Logs(Logs {
                 commands: Some(LogsCommands::Rm),
+                follow: false,
                 display: DisplayCommands {
                     json: false,
                     name: None,
@@ -247,6 +312,67 @@ mod display {
         assert_eq!(result, "logs rm");
     }
     #[test]
+    fn logs_follow_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Logs(Logs {
+                commands: None,
+                follow: true,
+                display: DisplayCommands {
+                    json: false,
+                    name: None,
+                    color: None,
+                },
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        assert_eq!(result, "logs --follow");
+    }
+    #[test]
+    fn logs_rm_follow_args() {
+        // Define a cli struct
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Logs(Logs {
+                commands: Some(LogsCommands::Rm),
+                follow: true,
+                display: DisplayCommands {
+                    json: true,
+                    name: None,
+                    color: None,
+                },
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        assert_eq!(result, "logs rm --follow --json");
+    }
+    #[test]
+    fn artifacts_args() {
+        let cli = Cli {
+            commands: Commands::PostCommands(PostCommands::Artifacts(Artifacts {
+                name: Some("build".to_owned()),
+                run: None,
+                out: Some("dist".to_owned()),
+            })),
+            attach: Some(String::from(&Attach::False)),
+            raw: None,
+            config: None,
+            internal_verbose: InternalVerbosity::new(0, 0),
+            verbose: Verbosity::new(0, 0),
+        };
+        let result = format!("{}", cli);
+        assert_eq!(result, "artifacts build --out dist");
+    }
+    #[test]
     fn internal_verbosity() {
         // Define a cli struct
         let cli = Cli {
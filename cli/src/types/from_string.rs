@@ -2,7 +2,7 @@ use crate::types::{Attach, ColoredOutput};
 use convert_case::{Case, Casing};
 // Structs
 use crate::types::Commands;
-use crate::types::{Cli, DisplayCommands, Init, Logs, Pipeline, Shell, Toggle, Trigger};
+use crate::types::{Artifacts, Cli, DisplayCommands, Init, Logs, Pipeline, Shell, Toggle, Trigger};
 
 impl From<&String> for Attach {
     fn from(option: &String) -> Attach {
@@ -65,6 +65,12 @@ impl From<&Logs> for String {
     }
 }
 
+impl From<&Artifacts> for String {
+    fn from(e: &Artifacts) -> String {
+        format!("{}", &e)
+    }
+}
+
 impl From<&Toggle> for String {
     fn from(e: &Toggle) -> String {
         format!("{}", &e)
@@ -163,6 +163,8 @@ pub enum PostCommands {
     Stop(Pipeline),
     /// Display pipelines logs
     Logs(Logs),
+    /// Retrieve the files collected by a pipeline run
+    Artifacts(Artifacts),
     /// List available pipelines with a few more useful informations
     Ls(DisplayCommands),
     /// Displays pipelines with the maximum verbosity level (interactive)
@@ -178,6 +180,8 @@ pub enum DetachableCommands {
     Run(Pipeline),
     /// Manualy trigger pipelines
     Trigger(Trigger),
+    /// Repeat a previous pipeline run
+    Rerun(Rerun),
     /// Launch a watcher on the working directory (debugging)
     #[command(hide = true)]
     Watch,
@@ -201,6 +205,18 @@ pub struct Pipeline {
     pub name: Option<String>,
     #[command(flatten)]
     pub trigger: Trigger,
+
+    /// Start from this step, reusing the previous ones from the last run
+    #[arg(long, value_name = "STEP")]
+    pub from: Option<String>,
+
+    /// Resume a previous run from its first failed step
+    #[arg(long, value_name = "UUID")]
+    pub resume: Option<String>,
+
+    /// Repeat a previous run in its triggering environment
+    #[arg(long, value_name = "UUID", hide = true)]
+    pub rerun: Option<String>,
 }
 impl Default for Pipeline {
     fn default() -> Self {
@@ -208,19 +224,40 @@ impl Default for Pipeline {
             name: Some("default".to_owned()),
             trigger: Trigger {
                 flag: Some("blank".to_owned()),
+                ..Trigger::default()
             },
+            from: None,
+            resume: None,
+            rerun: None,
         }
     }
 }
 
 /**
-Arguments to set/modify the triggering environment.
+Arguments to repeat a pipeline run.
 */
 #[derive(Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Rerun {
+    /// The run uuid
+    pub uuid: String,
+}
+
+/**
+Arguments to set/modify the triggering environment.
+*/
+#[derive(Debug, Default, Clone, Eq, PartialEq, Parser)]
 pub struct Trigger {
     /// Manualy set a flag/action to bypass environment computation.
     #[arg(long, ignore_case = true)]
     pub flag: Option<String>,
+
+    /// Print the execution plan without running anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print the execution plan in json format
+    #[arg(long, requires = "dry_run")]
+    pub json: bool,
 }
 /**
 Arguments to query logs.
@@ -231,11 +268,32 @@ pub struct Logs {
     #[command(subcommand)]
     pub commands: Option<LogsCommands>,
 
+    /// Follow the most recent running run until it finishes
+    #[arg(long, short)]
+    pub follow: bool,
+
     /// Display logs in json format
     #[command(flatten)]
     pub display: DisplayCommands,
 }
 
+/**
+Arguments to retrieve a pipeline run artifacts.
+*/
+#[derive(Debug, Clone, Eq, PartialEq, Parser)]
+pub struct Artifacts {
+    /// The pipeline name
+    pub name: Option<String>,
+
+    /// The run uuid, defaults to the last run
+    #[arg(long)]
+    pub run: Option<String>,
+
+    /// Copy the artifacts into this directory
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub out: Option<String>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Parser)]
 pub enum LogsCommands {
     /// Clear logs
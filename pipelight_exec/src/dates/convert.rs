@@ -77,6 +77,52 @@ pub fn std_duration_to_human_duration(duration: std::time::Duration) -> Result<S
     Ok(res)
 }
 
+/**
+Convert a human readable duration string (ex: "10m", "1h30m", "45s", "500ms")
+into the standard duration struct(std::time::Duration).
+
+Available units are d, h, m, s and ms.
+*/
+pub fn human_duration_to_std_duration(duration: &str) -> Result<std::time::Duration> {
+    let message = format!(
+        "Couldn't parse duration: {:?}, expected a number followed by a unit (d, h, m, s, ms)",
+        duration
+    );
+    let mut res = std::time::Duration::ZERO;
+    let mut chars = duration.trim().chars().peekable();
+    if chars.peek().is_none() {
+        return Err(Error::msg(message));
+    }
+    while chars.peek().is_some() {
+        // Value
+        let mut value = "".to_owned();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            value.push(*c);
+            chars.next();
+        }
+        // Unit
+        let mut unit = "".to_owned();
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+            unit.push(*c);
+            chars.next();
+        }
+        let value: u64 = value.parse().map_err(|_| Error::msg(message.clone()))?;
+        let seconds = match unit.as_str() {
+            "d" => 24 * 60 * 60,
+            "h" => 60 * 60,
+            "m" => 60,
+            "s" => 1,
+            "ms" => {
+                res += std::time::Duration::from_millis(value);
+                continue;
+            }
+            _ => return Err(Error::msg(message)),
+        };
+        res += std::time::Duration::from_secs(value * seconds);
+    }
+    Ok(res)
+}
+
 #[cfg(test)]
 mod convert {
     use crate::dates::convert::*;
@@ -88,6 +134,15 @@ mod convert {
         iso8601_to_std_duration(&string).unwrap();
     }
     #[test]
+    fn human_to_duration() {
+        let std = human_duration_to_std_duration("1h30m").unwrap();
+        assert_eq!(std, time::Duration::from_secs(5400));
+        let std = human_duration_to_std_duration("500ms").unwrap();
+        assert_eq!(std, time::Duration::from_millis(500));
+        assert!(human_duration_to_std_duration("10 minutes").is_err());
+        assert!(human_duration_to_std_duration("m").is_err());
+    }
+    #[test]
     fn duration_to_string() {
         let std = time::Duration::new(5, 0);
         std_duration_to_iso8601(&std).unwrap();
@@ -2,10 +2,12 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 // Globals
 use crate::globals::OUTDIR;
+// Struct
+use crate::{Line, Stream};
 // File manipulation
-use std::fs::{remove_dir_all, File};
+use std::fs::{remove_dir_all, write, File};
 use std::io::BufReader;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use std::process::Output;
 
@@ -39,7 +41,8 @@ impl Io {
         Ok(())
     }
     /**
-     * Read the process stdout and stderr and stores it in the struct field
+     * Read the process stdout and stderr and stores it in the struct field,
+     * as written by the process, None if empty (as when read from the pipes).
      *
      *
      * ```rust,ignore
@@ -76,38 +79,159 @@ impl Io {
 
         *self = Io {
             stdin: self.stdin.to_owned(),
-            stdout: Some(stdout),
-            stderr: Some(stderr),
+            stdout: to_text(stdout),
+            stderr: to_text(stderr),
             ..*self
         };
         Ok(())
     }
+    /**
+    Read the lines appended to the process stdout and stderr files
+    since the given byte offsets, and move the offsets past them.
+
+    A trailing partial line is left for the next read, unless flushed
+    (once the process has exited).
+    Missing files (the process has not started yet) have no lines.
+    */
+    pub fn read_new_lines(
+        &self,
+        offsets: &mut [u64; 2],
+        flush: bool,
+    ) -> Result<Vec<Line>, std::io::Error> {
+        let mut lines = vec![];
+        for (i, stream) in [Stream::Stdout, Stream::Stderr].into_iter().enumerate() {
+            let path = format!("{}/{}/{}", *OUTDIR.lock().unwrap(), self.uuid, i + 1);
+            let mut f = match File::open(path) {
+                Ok(f) => f,
+                Err(_) => continue,
+            };
+            f.seek(SeekFrom::Start(offsets[i]))?;
+            let mut buf = vec![];
+            f.read_to_end(&mut buf)?;
+            let end = match flush {
+                true => buf.len(),
+                false => buf.iter().rposition(|e| *e == b'\n').map_or(0, |e| e + 1),
+            };
+            offsets[i] += end as u64;
+            for text in String::from_utf8_lossy(&buf[..end]).lines() {
+                lines.push(Line {
+                    stream: stream.clone(),
+                    text: text.trim_end_matches('\r').to_owned(),
+                });
+            }
+        }
+        Ok(lines)
+    }
+    /**
+    Overwrite the process stdout and stderr files with the struct fields.
+    */
+    pub fn write(&self) -> Result<(), std::io::Error> {
+        let stdout_path = format!("{}/{}/1", *OUTDIR.lock().unwrap(), self.uuid);
+        let stderr_path = format!("{}/{}/2", *OUTDIR.lock().unwrap(), self.uuid);
+        write(stdout_path, self.stdout.clone().unwrap_or_default())?;
+        write(stderr_path, self.stderr.clone().unwrap_or_default())?;
+        Ok(())
+    }
+    /**
+    Replace every occurrence of the given values
+    in the process stdin, stdout and stderr with a mask.
+    */
+    pub fn redact(&mut self, values: &[String]) {
+        for text in [&mut self.stdin, &mut self.stdout, &mut self.stderr]
+            .into_iter()
+            .flatten()
+        {
+            *text = redact(text, values);
+        }
+    }
+}
+
+/**
+The string that replaces secret values.
+*/
+pub static MASK: &str = "***";
+
+/**
+Replace every occurrence of the given values in a text with a mask.
+Longer values are replaced first,
+so that a value containing another one is fully masked.
+*/
+pub fn redact(text: &str, values: &[String]) -> String {
+    let mut values: Vec<&String> = values.iter().filter(|e| !e.is_empty()).collect();
+    values.sort_by_key(|e| std::cmp::Reverse(e.len()));
+    let mut text = text.to_owned();
+    for value in values {
+        text = text.replace(value.as_str(), MASK);
+    }
+    text
 }
 /**
 * Convert a standart process (std::process) outputs into an Io struct.
 * The output buffers are converted into human readable strings.
 */
+/**
+* A process output, trailing newline included, None if empty.
+*/
+fn to_text(text: String) -> Option<String> {
+    match text.is_empty() {
+        true => None,
+        false => Some(text),
+    }
+}
+
 impl From<&Output> for Io {
     fn from(output: &Output) -> Io {
-        let stdout_str = String::from_utf8(output.stdout.to_owned()).unwrap();
-        let stdout_str = stdout_str.trim_matches('\n').trim_matches('\r');
-
-        let stderr_str = String::from_utf8(output.stderr.to_owned()).unwrap();
-        let stderr_str = stderr_str.trim_matches('\n').trim_matches('\r');
-
-        let mut stdout = None;
-        if !stdout_str.is_empty() {
-            stdout = Some(stdout_str.to_owned());
-        }
-        let mut stderr = None;
-        if !stderr_str.is_empty() {
-            stderr = Some(stderr_str.to_owned());
-        }
+        let stdout_str = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr_str = String::from_utf8_lossy(&output.stderr).into_owned();
         Io {
             stdin: None,
             uuid: Uuid::new_v4(),
-            stdout,
-            stderr,
+            stdout: to_text(stdout_str),
+            stderr: to_text(stderr_str),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{Process, Stream};
+    use miette::{IntoDiagnostic, Result};
+
+    #[test]
+    fn read_new_lines() -> Result<()> {
+        let proc = Process::new()
+            .stdin("printf 'one\\npartial'; echo err >&2")
+            .term()
+            .fs()
+            .run()?;
+        let mut offsets = [0, 0];
+        let lines = proc
+            .io
+            .read_new_lines(&mut offsets, false)
+            .into_diagnostic()?;
+        let texts: Vec<(Stream, &str)> = lines
+            .iter()
+            .map(|e| (e.stream.clone(), e.text.as_str()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![(Stream::Stdout, "one"), (Stream::Stderr, "err")]
+        );
+        assert_eq!(offsets, [4, 4]);
+
+        // The partial line is read once flushed, and only once
+        let lines = proc
+            .io
+            .read_new_lines(&mut offsets, true)
+            .into_diagnostic()?;
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].text, "partial");
+        let lines = proc
+            .io
+            .read_new_lines(&mut offsets, true)
+            .into_diagnostic()?;
+        assert!(lines.is_empty());
+        proc.io.clean().into_diagnostic()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/**
+* A cancellation flag, shared between the threads that raise it
+* and the threads that wait for processes.
+*
+* Once raised, the thread that waits for a watching process
+* kills the process group.
+* Clones share the same flag.
+*/
+#[derive(Debug, Default, Clone)]
+pub struct Cancel(Arc<AtomicBool>);
+
+impl Cancel {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn raise(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+    pub fn lower(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+    pub fn is_raised(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+/**
+* Flags are equal when they are shared.
+*/
+impl PartialEq for Cancel {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for Cancel {}
@@ -18,6 +18,7 @@ pub struct Finder {
     root: Option<String>,
     cwd: Option<String>,
     pid: Option<u32>,
+    ppid: Option<u32>,
     gid: Option<u32>,
     // Search results
     pub matches: Option<Vec<crate::Process>>,
@@ -31,6 +32,7 @@ impl Default for Finder {
             cwd: None,
             seeds: None,
             pid: None,
+            ppid: None,
             gid: None,
             matches: None,
         }
@@ -79,6 +81,23 @@ impl Finder {
         self.pid = Some(pid.to_owned());
         self.to_owned()
     }
+    /**
+     * Restrict search result by parent pid.
+     */
+    pub fn ppid(&mut self, ppid: &u32) -> Self {
+        self.ppid = Some(ppid.to_owned());
+        self.to_owned()
+    }
+    /**
+     * Guard - Ensure process is a child of the provided parent pid.
+     */
+    fn is_child(&mut self, process: &Process) -> bool {
+        match (self.ppid, process.parent()) {
+            (Some(ppid), Some(parent)) => parent.as_u32() == ppid,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
     /**
      * Guard - Ensure command contains some seed(string)
      */
@@ -168,8 +187,11 @@ impl Finder {
                 // Guard - Ensure command contains some seed(string)
                 let cond_seed = self.is_match_seeds(process)?;
 
+                // Guard - Ensure process is a child of the provided parent (ppid)
+                let cond_ppid = self.is_child(process);
+
                 // Final resolution
-                if cond_root && cond_pwd && cond_seed && cond_other_pid {
+                if cond_root && cond_pwd && cond_seed && cond_other_pid && cond_ppid {
                     matches.push(crate::Process::from(process));
                 }
             }
@@ -329,6 +351,29 @@ mod test {
         Ok(())
     }
 
+    /**
+     * Run a background process and find it back by its parent pid.
+     */
+    #[test]
+    fn children() -> Result<(), PipelightError> {
+        let mut process = Process::new()
+            .stdin("sleep 3")
+            .background()
+            .detach()
+            .to_owned();
+        process.run()?;
+
+        let finder = Finder::new()
+            .ppid(&std::process::id())
+            .seed("sleep 3")
+            .search()?;
+
+        finder.kill()?;
+        assert_eq!(finder.clone().matches.unwrap().len(), 1);
+
+        Ok(())
+    }
+
     // #[test]
     fn long_seed() -> Result<(), PipelightError> {
         let mut process = Process::new()
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::fs;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use uuid::Uuid;
+
+// Unix process manipulation
+use rustix::process::{getrlimit, setrlimit, Resource, Rlimit, Signal};
+
+// Struct
+use super::sandbox::write_file;
+use crate::Reason;
+
+// Error Handling
+use miette::{Error, Result};
+
+/**
+* The cgroup v2 unified hierarchy mount point.
+*/
+static CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/**
+* Resource limits of a process and its children.
+*
+* Limits are set with setrlimit right before exec.
+* Memory and processes are bounded by a dedicated cgroup v2 instead
+* when the current cgroup delegates those controllers to its children.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Limits {
+    // Maximum memory, in bytes.
+    pub memory: Option<u64>,
+    // Maximum CPU time, in seconds.
+    pub cpu_time: Option<u64>,
+    // Maximum number of open files.
+    pub nofile: Option<u64>,
+    // Maximum number of processes,
+    // of the user when the command has no dedicated cgroup.
+    pub nproc: Option<u64>,
+}
+
+impl Limits {
+    /**
+     * Bound the resources of the spawned command.
+     * Limits can only be lowered: they are capped by the current hard limits.
+     */
+    pub(crate) fn apply(&self, cmd: &mut Command, cgroup: Option<&Cgroup>) {
+        let mut rlimits: Vec<(Resource, Rlimit)> = vec![];
+        // The cgroup bounds the actual memory usage and the command processes,
+        // the address space and user processes limits are only fallbacks.
+        if cgroup.is_none() {
+            if let Some(memory) = self.memory {
+                rlimits.push(capped(Resource::As, memory, memory));
+            }
+            if let Some(nproc) = self.nproc {
+                rlimits.push(capped(Resource::Nproc, nproc, nproc));
+            }
+        }
+        if let Some(cpu_time) = self.cpu_time {
+            // SIGXCPU on the soft limit, SIGKILL a second later.
+            rlimits.push(capped(Resource::Cpu, cpu_time, cpu_time + 1));
+        }
+        if let Some(nofile) = self.nofile {
+            rlimits.push(capped(Resource::Nofile, nofile, nofile));
+        }
+        let procs = cgroup.map(|e| e.procs.clone());
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(procs) = &procs {
+                    // "0" stands for the writing process.
+                    write_file(procs.as_c_str(), b"0")?;
+                }
+                for (resource, rlimit) in &rlimits {
+                    setrlimit(*resource, *rlimit)?;
+                }
+                Ok(())
+            });
+        }
+    }
+    /**
+     * Report which limit, if any, made the process fail.
+     * Relies on the cgroup events and on the exit signal only:
+     * a failure caused by an rlimit that isn't signaled can't be told apart.
+     */
+    pub(crate) fn exceeded(&self, status: &ExitStatus, cgroup: Option<&Cgroup>) -> Option<Reason> {
+        if let Some(cgroup) = cgroup {
+            if self.memory.is_some() && cgroup.count("memory.events", "oom_kill") > 0 {
+                return Some(Reason::MemoryLimit);
+            }
+            if self.nproc.is_some() && cgroup.count("pids.events", "max") > 0 {
+                return Some(Reason::NprocLimit);
+            }
+        }
+        if status.success() {
+            return None;
+        }
+        let xcpu = Signal::Xcpu as i32;
+        if self.cpu_time.is_some()
+            && (status.signal() == Some(xcpu) || status.code() == Some(128 + xcpu))
+        {
+            return Some(Reason::CpuTimeLimit);
+        }
+        None
+    }
+}
+
+/**
+* A limit lowered to the current hard limit.
+*/
+fn capped(resource: Resource, current: u64, maximum: u64) -> (Resource, Rlimit) {
+    let hard = getrlimit(resource).maximum;
+    let cap = |e: u64| Some(hard.map_or(e, |hard| e.min(hard)));
+    let rlimit = Rlimit {
+        current: cap(current),
+        maximum: cap(maximum),
+    };
+    (resource, rlimit)
+}
+
+/**
+* A dedicated cgroup v2, child of the current process one.
+*/
+#[derive(Debug)]
+pub(crate) struct Cgroup {
+    path: PathBuf,
+    procs: CString,
+}
+
+impl Cgroup {
+    /**
+     * Create a cgroup that bounds the memory and processes of the command.
+     *
+     * Only a subtree delegated to this process is used:
+     * the current cgroup must already enable the needed controllers
+     * for its children, the host hierarchy is never modified.
+     * Returns None otherwise, the limits then fall back to rlimits.
+     */
+    pub(crate) fn create(uuid: &Uuid, limits: &Limits) -> Option<Cgroup> {
+        let mut controllers = vec![];
+        if limits.memory.is_some() {
+            controllers.push("memory");
+        }
+        if limits.nproc.is_some() {
+            controllers.push("pids");
+        }
+        let root = Path::new(CGROUP_ROOT);
+        if controllers.is_empty() || !root.join("cgroup.controllers").is_file() {
+            return None;
+        }
+        let own = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let own = own.lines().find_map(|e| e.strip_prefix("0::"))?;
+        let own = root.join(own.trim_start_matches('/'));
+
+        // Controllers must be delegated to the current cgroup children
+        let subtree = fs::read_to_string(own.join("cgroup.subtree_control")).ok()?;
+        if !controllers
+            .iter()
+            .all(|e| subtree.split_whitespace().any(|c| c == *e))
+        {
+            return None;
+        }
+
+        let path = own.join(format!("pipelight-{}", uuid));
+        fs::create_dir(&path).ok()?;
+        let cgroup = Cgroup {
+            procs: CString::new(path.join("cgroup.procs").display().to_string()).ok()?,
+            path,
+        };
+        let mut res = Ok(());
+        if let Some(memory) = limits.memory {
+            res = res.and(fs::write(
+                cgroup.path.join("memory.max"),
+                memory.to_string(),
+            ));
+            // Don't swap instead of being killed
+            fs::write(cgroup.path.join("memory.swap.max"), "0").ok();
+        }
+        if let Some(nproc) = limits.nproc {
+            res = res.and(fs::write(cgroup.path.join("pids.max"), nproc.to_string()));
+        }
+        if res.is_err() {
+            cgroup.release();
+            return None;
+        }
+        Some(cgroup)
+    }
+    /**
+     * Read a counter from a cgroup events file.
+     */
+    fn count(&self, file: &str, key: &str) -> u64 {
+        fs::read_to_string(self.path.join(file))
+            .unwrap_or_default()
+            .lines()
+            .find_map(|e| e.strip_prefix(&format!("{} ", key))?.trim().parse().ok())
+            .unwrap_or_default()
+    }
+    /**
+     * Remove the cgroup, once its processes have exited.
+     */
+    pub(crate) fn release(&self) {
+        fs::remove_dir(&self.path).ok();
+    }
+}
+
+/**
+* Convert a human readable size (ex: "512M", "2G") into bytes.
+* Units are powers of 1024, a bare number is a count of bytes.
+*/
+pub fn human_size_to_bytes(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let (number, unit) = size.split_at(
+        size.find(|e: char| !e.is_ascii_digit())
+            .unwrap_or(size.len()),
+    );
+    let number: u64 = number
+        .parse()
+        .map_err(|_| Error::msg(format!("Couldn't parse size: {:?}", size)))?;
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(Error::msg(format!("Unknown size unit: {:?}", unit))),
+    };
+    number
+        .checked_mul(multiplier)
+        .ok_or(Error::msg(format!("Size too large: {:?}", size)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn human_size() -> Result<()> {
+        assert_eq!(human_size_to_bytes("4096")?, 4096);
+        assert_eq!(human_size_to_bytes("512M")?, 512 * 1024 * 1024);
+        assert_eq!(human_size_to_bytes("2G")?, 2 * 1024 * 1024 * 1024);
+        assert!(human_size_to_bytes("2X").is_err());
+        assert!(human_size_to_bytes("G").is_err());
+        Ok(())
+    }
+}
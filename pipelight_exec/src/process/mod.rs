@@ -1,24 +1,45 @@
 // Tests
 
+mod cancel;
 #[cfg(feature = "fd")]
 mod fd;
 mod finder;
+mod limits;
 mod run;
+mod sandbox;
+mod stream;
 
 // Re-export
+pub use cancel::Cancel;
 pub use finder::Finder;
+pub use limits::{human_size_to_bytes, Limits};
+pub use sandbox::{Sandbox, SCRATCH_DIR};
+pub use stream::{Line, Stream};
 
 use bon::{bon, builder};
 
 // Unix process manipulation
+use rustix::process::{getpgid, Pid};
 use sysinfo::get_current_pid;
 use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 // Struct
 use crate::{Io, State};
 
+/**
+* How a child process is detached from its parent.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+enum Detach {
+    // Keep the parent process group and session.
+    Soft,
+    // Run in a new session and process group.
+    Hard,
+}
+
 /**
 * A struct that stores the process attributes for further access and manipulation.
 */
@@ -27,8 +48,16 @@ pub struct Runner {
     uuid: Uuid,
     term: bool,
     background: bool,
-    detach: bool,
+    detach: Option<Detach>,
     fs: bool,
+    group: bool,
+    timeout: Option<std::time::Duration>,
+    env: BTreeMap<String, String>,
+    secrets: Vec<String>,
+    sandbox: Option<Sandbox>,
+    limits: Option<Limits>,
+    #[serde(skip)]
+    cancel: Vec<Cancel>,
 }
 impl Default for Runner {
     fn default() -> Self {
@@ -36,8 +65,15 @@ impl Default for Runner {
             uuid: Uuid::new_v4(),
             term: false,
             background: false,
-            detach: false,
+            detach: None,
             fs: false,
+            group: false,
+            timeout: None,
+            env: BTreeMap::new(),
+            secrets: vec![],
+            sandbox: None,
+            limits: None,
+            cancel: vec![],
         }
     }
 }
@@ -48,8 +84,15 @@ impl Runner {
             uuid,
             term: false,
             background: false,
-            detach: false,
+            detach: None,
             fs: false,
+            group: false,
+            timeout: None,
+            env: BTreeMap::new(),
+            secrets: vec![],
+            sandbox: None,
+            limits: None,
+            cancel: vec![],
         }
     }
 }
@@ -82,6 +125,9 @@ pub struct Process {
     // process parent id
     pub ppid: Option<i32>,
     pub gid: Option<i32>,
+    // process group id
+    pub pgid: Option<i32>,
+    // session id
     pub sid: Option<i32>,
     pub state: State,
     pub io: Io,
@@ -99,6 +145,7 @@ impl Default for Process {
             pid: None,
             ppid: None,
             gid: None,
+            pgid: None,
             sid: None,
             cwd: None,
             io: Io {
@@ -121,6 +168,14 @@ impl Process {
         self.io.stdin = Some(stdin.to_owned());
         self
     }
+    /**
+     * Set the process working directory.
+     * A relative path is resolved from the current directory.
+     */
+    pub fn current_dir(&mut self, dir: &str) -> &mut Self {
+        self.cwd = Some(dir.to_owned());
+        self
+    }
     pub fn term(&mut self) -> &mut Self {
         self.config.term = true;
         self
@@ -129,31 +184,90 @@ impl Process {
         self.config.background = true;
         self
     }
+    /**
+     * Same as soft_detach.
+     */
     pub fn detach(&mut self) -> &mut Self {
-        self.config.detach = true;
-        self
+        self.soft_detach()
     }
     /**
      * Detach child process from parent but keep in process group.
      * Killing parent won't kill child but killing parent group will.
      */
     pub fn soft_detach(&mut self) -> &mut Self {
-        self.config.detach = true;
+        self.config.detach = Some(Detach::Soft);
         self
     }
     /**
      * Detach child process from parent and remove from process group
+     * by running it in a new session (setsid).
      *
      * Neither killing parent or parent group will kill the child.
      */
     pub fn hard_detach(&mut self) -> &mut Self {
-        self.config.detach = true;
+        self.config.detach = Some(Detach::Hard);
         self
     }
     pub fn fs(&mut self) -> &mut Self {
         self.config.fs = true;
         self
     }
+    /**
+     * Run the process in its own process group,
+     * so that it can be killed along with its children.
+     */
+    pub fn group(&mut self) -> &mut Self {
+        self.config.group = true;
+        self
+    }
+    /**
+     * Bound the process execution time.
+     * The process runs in its own process group
+     * and the whole group is killed when the timeout is reached.
+     */
+    pub fn timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+    /**
+     * Add environment variables to the process.
+     * They override the inherited parent environment.
+     */
+    pub fn envs(&mut self, env: &BTreeMap<String, String>) -> &mut Self {
+        self.config.env.extend(env.clone());
+        self
+    }
+    /**
+     * Mask the given values in the process inputs and outputs,
+     * temporary output files included, once the process has run.
+     */
+    pub fn secrets(&mut self, values: &[String]) -> &mut Self {
+        self.config.secrets.extend(values.iter().cloned());
+        self
+    }
+    /**
+     * Isolate the process from the host with Linux namespaces.
+     */
+    pub fn sandbox(&mut self, sandbox: &Sandbox) -> &mut Self {
+        self.config.sandbox = Some(sandbox.to_owned());
+        self
+    }
+    /**
+     * Bound the process resources (memory, cpu time, open files, processes).
+     * A failure caused by a limit is reported in the process state reason.
+     */
+    pub fn limits(&mut self, limits: &Limits) -> &mut Self {
+        self.config.limits = Some(limits.to_owned());
+        self
+    }
+    /**
+     * Kill the process, and its process group, once the flag is raised.
+     * A process can watch several flags.
+     */
+    pub fn cancel_on(&mut self, flag: &Cancel) -> &mut Self {
+        self.config.cancel.push(flag.to_owned());
+        self
+    }
 }
 
 impl Process {
@@ -183,6 +297,9 @@ impl From<&sysinfo::Process> for Process {
             pid: Some(proc.pid().as_u32() as i32),
             ppid: Some(proc.parent().unwrap().as_u32() as i32),
             gid: Some(*proc.group_id().unwrap().to_owned() as i32),
+            pgid: getpgid(Pid::from_raw(proc.pid().as_u32() as i32))
+                .ok()
+                .map(|e| Pid::as_raw(Some(e))),
             sid: Some(proc.session_id().unwrap().as_u32() as i32),
             ..Process::new()
                 .stdin(
@@ -1,15 +1,22 @@
+use super::limits::Cgroup;
+use super::{Detach, Line};
 use crate::dates::Duration;
-use crate::{Io, Process, State, Status};
+use crate::{Io, Process, Sandbox, State, Status};
 
 // Globals
 use crate::globals::{get_shell, OUTDIR, SHELL};
 
 // Unix process manipulation
+use rustix::process::{getpgid, getsid, kill_process_group, setsid, Pid, Signal};
 use std::os::unix::process::CommandExt;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::{thread, time};
 
 // File manipulation
 use std::fs::{create_dir_all, File};
+use std::io::Read;
 
 // Error Handling
 use log::info;
@@ -18,6 +25,41 @@ use pipelight_error::{PipelightError, WrapError};
 
 impl Process {
     pub fn run(&mut self) -> Result<Self, PipelightError> {
+        self.execute(None)
+    }
+    /**
+     * Run the process and call `on_line` on every output line
+     * as soon as it is printed, secrets masked.
+     *
+     * Outputs are still collected into the process io
+     * and teed into the process files when `fs()` is set.
+     * A background process is spawned without streaming.
+     *
+     * Example:
+     *
+     * ```rust
+     * # use pipelight_exec::Process;
+     * # use miette::Report;
+     *
+     * let proc = Process::new()
+     *   .stdin("echo one; sleep 1; echo two")
+     *   .term()
+     *   .stream(|line| println!("{}", line.text))?;
+     *
+     * # Ok::<(), Report>(())
+     * ```
+     */
+    pub fn stream(&mut self, mut on_line: impl FnMut(&Line)) -> Result<Self, PipelightError> {
+        self.execute(Some(&mut on_line))
+    }
+    /**
+     * Spawn the process and wait for it (unless in the background),
+     * forwarding its output lines to the callback if any.
+     */
+    pub(super) fn execute(
+        &mut self,
+        on_line: Option<&mut dyn FnMut(&Line)>,
+    ) -> Result<Self, PipelightError> {
         // Generate command
         let mut cmd = match self.config.term {
             false => self.to_command(),
@@ -29,7 +71,15 @@ impl Process {
         };
         cmd.stdin(Stdio::null())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            .envs(&self.config.env);
+
+        // Working directory, if any, recorded as an absolute path
+        if let Some(cwd) = &self.cwd {
+            let cwd = std::env::current_dir()?.join(cwd).canonicalize()?;
+            cmd.current_dir(&cwd);
+            self.cwd = Some(cwd.display().to_string());
+        }
 
         // Output redirection
         match self.config.fs {
@@ -37,25 +87,58 @@ impl Process {
                 let proc_path = format!("{}/{}", *OUTDIR.lock().unwrap(), self.uuid);
                 create_dir_all(&proc_path)?;
 
-                let stdout_path = format!("{proc_path}/1");
-                let stderr_path = format!("{proc_path}/2");
-                cmd.stdout(File::create(stdout_path)?)
-                    .stderr(File::create(stderr_path)?);
+                // Streamed outputs are teed into the files instead
+                if on_line.is_none() {
+                    let stdout_path = format!("{proc_path}/1");
+                    let stderr_path = format!("{proc_path}/2");
+                    cmd.stdout(File::create(stdout_path)?)
+                        .stderr(File::create(stderr_path)?);
+                }
             }
             false => {}
         }
 
         match self.config.detach {
-            true => {
-                // Hard detach
-                // cmd.process_group(0);
-
-                // Soft detach
+            Some(Detach::Soft) => {
                 cmd.stdout(Stdio::null()).stderr(Stdio::null());
             }
-            false => {}
+            Some(Detach::Hard) => {
+                cmd.stdout(Stdio::null()).stderr(Stdio::null());
+                // New session and process group, without controlling terminal
+                unsafe {
+                    cmd.pre_exec(|| {
+                        setsid()?;
+                        Ok(())
+                    });
+                }
+            }
+            None => {}
         };
 
+        // Run in a dedicated process group to kill the whole group
+        // on demand, on timeout or on cancellation.
+        // A new session already comes with its own process group.
+        if (self.config.group || self.config.timeout.is_some() || !self.config.cancel.is_empty())
+            && self.config.detach != Some(Detach::Hard)
+        {
+            cmd.process_group(0);
+        }
+
+        // Bound the resources, before entering a sandbox
+        let mut cgroup = None;
+        if let Some(limits) = &self.config.limits {
+            // Only a waiting parent can release the cgroup
+            if !self.config.background {
+                cgroup = Cgroup::create(&self.uuid, limits);
+            }
+            limits.apply(&mut cmd, cgroup.as_ref());
+        }
+
+        // Isolate from the host
+        if let Some(sandbox) = &self.config.sandbox {
+            sandbox.apply(&mut cmd)?;
+        }
+
         // Process execution
         // and catch child pid
 
@@ -64,31 +147,120 @@ impl Process {
 
         if self.config.background {
             duration.start();
-            cmd.spawn()?;
+            let child = cmd.spawn()?;
             duration.stop();
+            self.set_ids(child.id());
         } else {
-            let child = cmd.spawn()?;
-            self.pid = Some(child.id().to_owned() as i32);
+            let child = cmd.spawn().inspect_err(|_| {
+                if let Some(cgroup) = &cgroup {
+                    cgroup.release();
+                }
+            })?;
+            self.set_ids(child.id());
 
             duration.start();
-            let output = child.wait_with_output()?;
+            let streamed = on_line.is_some();
+            let polled = self.config.timeout.is_some() || !self.config.cancel.is_empty();
+            let (output, timed_out) = match on_line {
+                Some(on_line) => self.wait_streamed(child, on_line)?,
+                None if polled => self.wait_polled(child)?,
+                None => (child.wait_with_output()?, false),
+            };
             duration.stop();
             self.io = Io {
                 uuid: self.io.uuid,
                 stdin: self.io.stdin.to_owned(),
                 ..Io::from(&output)
             };
+            let status = match timed_out {
+                true => Status::TimedOut,
+                false => Status::from(&output),
+            };
             self.state = State {
                 duration: Some(duration),
-                status: Some(Status::from(&output)),
+                status: Some(status),
+                reason: None,
             };
-            if self.config.fs {
+            // Streamed outputs have already been read from the pipes
+            if self.config.fs && !streamed {
                 self.io.read()?;
                 // self.io.clean()?;
             }
+            if let Some(limits) = &self.config.limits {
+                if self.state.status == Some(Status::Failed) {
+                    self.state.reason = limits.exceeded(&output.status, cgroup.as_ref());
+                }
+            }
+            if let Some(cgroup) = &cgroup {
+                cgroup.release();
+            }
+            if !self.config.secrets.is_empty() {
+                self.io.redact(&self.config.secrets);
+                if self.config.fs {
+                    self.io.write()?;
+                }
+            }
         }
         Ok(self.to_owned())
     }
+    /**
+     * Wait for the child process to exit and collect its outputs.
+     * Kill the child process group if it runs longer than the timeout
+     * or once it is cancelled.
+     *
+     * Returns the process output and whether it has timed out.
+     */
+    fn wait_polled(&self, mut child: Child) -> Result<(Output, bool), PipelightError> {
+        // Read pipes concurrently so that the child never blocks on a full buffer.
+        // Every read chunk is notified back to the calling thread.
+        let (sender, receiver) = channel::<()>();
+        let (stdout, stderr) = (Arc::default(), Arc::default());
+        if let Some(pipe) = child.stdout.take() {
+            collect(pipe, sender.clone(), Arc::clone(&stdout));
+        }
+        if let Some(pipe) = child.stderr.take() {
+            collect(pipe, sender.clone(), Arc::clone(&stderr));
+        }
+        drop(sender);
+
+        let start = time::Instant::now();
+        let throttle = time::Duration::from_millis(10);
+        let mut timed_out = false;
+        // Until the child exits
+        let status = loop {
+            if let Some(status) = self.poll(&mut child, &start, &mut timed_out)? {
+                break status;
+            }
+            thread::sleep(throttle);
+        };
+        // Until the pipes are drained, or stay silent:
+        // a backgrounded child may hold them open long after.
+        let idle = time::Duration::from_millis(50);
+        let deadline = time::Instant::now() + time::Duration::from_secs(1);
+        while time::Instant::now() < deadline {
+            if receiver.recv_timeout(idle).is_err() {
+                break;
+            }
+        }
+
+        let stdout = stdout.lock().unwrap().clone();
+        let stderr = stderr.lock().unwrap().clone();
+        let output = Output {
+            status,
+            stdout,
+            stderr,
+        };
+        Ok((output, timed_out))
+    }
+    /**
+     * Record the spawned child pid, process group and session.
+     */
+    fn set_ids(&mut self, pid: u32) {
+        self.pid = Some(pid as i32);
+        let pid = Pid::from_raw(pid as i32);
+        self.pgid = getpgid(pid).ok().map(|e| Pid::as_raw(Some(e)));
+        self.sid = getsid(pid).ok().map(|e| Pid::as_raw(Some(e)));
+    }
     fn to_command(&self) -> Command {
         // convert stdin
         let mut args: Vec<String> = self
@@ -107,10 +279,33 @@ impl Process {
     }
 }
 
+/**
+ * Read a pipe into a buffer on a separate thread,
+ * and notify every read chunk.
+ */
+fn collect(mut pipe: impl Read + Send + 'static, sender: Sender<()>, buf: Arc<Mutex<Vec<u8>>>) {
+    thread::spawn(move || -> std::io::Result<()> {
+        let mut chunk = [0; 4096];
+        loop {
+            let n = pipe.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            buf.lock().unwrap().extend_from_slice(&chunk[..n]);
+            // The receiver is gone once the pipes are drained
+            sender.send(()).ok();
+        }
+        Ok(())
+    });
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{Cancel, Limits, Reason};
+    use std::collections::BTreeMap;
     use std::{thread, time};
+    use uuid::Uuid;
 
     #[test]
     fn default() -> Result<()> {
@@ -135,12 +330,242 @@ mod test {
         Ok(())
     }
     #[test]
+    fn timeout() -> Result<()> {
+        let proc = Process::new()
+            .stdin("sleep 10")
+            .term()
+            .timeout(time::Duration::from_millis(500))
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::TimedOut));
+        Ok(())
+    }
+    #[test]
+    fn timeout_not_reached() -> Result<()> {
+        let proc = Process::new()
+            .stdin("echo test")
+            .timeout(time::Duration::from_secs(10))
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("test\n".to_owned()));
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn timeout_background_child() -> Result<()> {
+        let start = time::Instant::now();
+        // The backgrounded child holds the pipes open after the shell exits
+        let proc = Process::new()
+            .stdin("sleep 4 & echo started")
+            .term()
+            .timeout(time::Duration::from_secs(10))
+            .run()?;
+        assert!(start.elapsed() < time::Duration::from_secs(2));
+        assert_eq!(proc.io.stdout, Some("started\n".to_owned()));
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn cancel() -> Result<()> {
+        let flag = Cancel::new();
+        let raiser = {
+            let flag = flag.clone();
+            thread::spawn(move || {
+                thread::sleep(time::Duration::from_millis(300));
+                flag.raise();
+            })
+        };
+        let start = time::Instant::now();
+        let proc = Process::new()
+            .stdin("sleep 10")
+            .term()
+            .cancel_on(&Cancel::new())
+            .cancel_on(&flag)
+            .run()?;
+        raiser.join().unwrap();
+        // Killed by the raised flag
+        assert!(start.elapsed() < time::Duration::from_secs(5));
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn envs() -> Result<()> {
+        let env = BTreeMap::from([("PIPELIGHT_TEST_VAR".to_owned(), "value".to_owned())]);
+        let proc = Process::new()
+            .stdin("echo $PIPELIGHT_TEST_VAR")
+            .term()
+            .envs(&env)
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("value\n".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn current_dir() -> Result<()> {
+        let proc = Process::new().stdin("pwd").current_dir("src").run()?;
+        let expected = std::env::current_dir().unwrap().join("src");
+        assert_eq!(proc.io.stdout, Some(format!("{}\n", expected.display())));
+        assert_eq!(proc.cwd, Some(expected.display().to_string()));
+        Ok(())
+    }
+    #[test]
+    fn no_current_dir() -> Result<()> {
+        let proc = Process::new().stdin("pwd").run()?;
+        // The inherited working directory is left untouched
+        assert_eq!(proc.cwd, None);
+        Ok(())
+    }
+    #[test]
     fn fs() -> Result<()> {
         let proc = Process::new().stdin("echo test").fs().run()?;
         assert_eq!(proc.io.stdout, Some("test\n".to_owned()));
         Ok(())
     }
     #[test]
+    fn secrets() -> Result<()> {
+        let secrets = vec!["s3cr3t".to_owned()];
+        let mut proc = Process::new()
+            .stdin("echo token=s3cr3t")
+            .term()
+            .fs()
+            .secrets(&secrets)
+            .run()?;
+        assert_eq!(proc.io.stdin, Some("echo token=***".to_owned()));
+        assert_eq!(proc.io.stdout, Some("token=***\n".to_owned()));
+        // Temporary files are masked too
+        proc.io.stdout = None;
+        proc.io.read().into_diagnostic()?;
+        assert_eq!(proc.io.stdout, Some("token=***\n".to_owned()));
+        proc.io.clean().into_diagnostic()?;
+        Ok(())
+    }
+    #[test]
+    fn sandbox() -> Result<()> {
+        let proc = Process::new()
+            .stdin("echo $$; touch Cargo.toml || echo read-only; touch $PIPELIGHT_SCRATCH/file && echo scratch")
+            .term()
+            .sandbox(&Sandbox::default())
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        assert_eq!(proc.io.stdout, Some("1\nread-only\nscratch\n".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn sandbox_writable() -> Result<()> {
+        let dir = std::env::current_dir()
+            .into_diagnostic()?
+            .join("target/sandbox_writable");
+        std::fs::create_dir_all(&dir).into_diagnostic()?;
+        let sandbox = Sandbox {
+            writable: vec![dir.display().to_string()],
+            ..Sandbox::default()
+        };
+        let proc = Process::new()
+            .stdin(&format!("echo test > {}/file", dir.display()))
+            .term()
+            .sandbox(&sandbox)
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        assert!(dir.join("file").is_file());
+        Ok(())
+    }
+    #[test]
+    fn limits_cpu_time() -> Result<()> {
+        let limits = Limits {
+            cpu_time: Some(1),
+            ..Limits::default()
+        };
+        let proc = Process::new()
+            .stdin("while true; do :; done")
+            .term()
+            .limits(&limits)
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        assert_eq!(proc.state.reason, Some(Reason::CpuTimeLimit));
+        Ok(())
+    }
+    #[test]
+    fn limits_nofile() -> Result<()> {
+        let limits = Limits {
+            nofile: Some(8),
+            ..Limits::default()
+        };
+        // Open more files than allowed at once
+        let proc = Process::new()
+            .stdin(&format!("paste{}", " /dev/null".repeat(16)))
+            .term()
+            .limits(&limits)
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        // Not signaled: the failure reason can't be told
+        assert_eq!(proc.state.reason, None);
+        Ok(())
+    }
+    #[test]
+    fn limits_memory() -> Result<()> {
+        let limits = Limits {
+            memory: Some(64 * 1024 * 1024),
+            ..Limits::default()
+        };
+        // Out of memory kills are only reported by a dedicated cgroup
+        let delegated = match Cgroup::create(&Uuid::new_v4(), &limits) {
+            Some(cgroup) => {
+                cgroup.release();
+                true
+            }
+            None => false,
+        };
+        let proc = Process::new()
+            .stdin("sort < /dev/zero")
+            .term()
+            .limits(&limits)
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        match delegated {
+            true => assert_eq!(proc.state.reason, Some(Reason::MemoryLimit)),
+            false => assert_eq!(proc.state.reason, None),
+        }
+        Ok(())
+    }
+    #[test]
+    fn limits_not_reached() -> Result<()> {
+        let limits = Limits {
+            memory: Some(256 * 1024 * 1024),
+            cpu_time: Some(10),
+            nofile: Some(64),
+            nproc: Some(4096),
+        };
+        let proc = Process::new()
+            .stdin("echo test; exit 2")
+            .term()
+            .limits(&limits)
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("test\n".to_owned()));
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        assert_eq!(proc.state.reason, None);
+        Ok(())
+    }
+    #[test]
+    fn sandbox_network() -> Result<()> {
+        // Only the loopback interface without network
+        let proc = Process::new()
+            .stdin("grep -c : /proc/net/dev")
+            .term()
+            .sandbox(&Sandbox::default())
+            .run()?;
+        assert_eq!(proc.io.stdout, Some("1\n".to_owned()));
+        Ok(())
+    }
+    #[test]
+    fn sandbox_exit_code() -> Result<()> {
+        let proc = Process::new()
+            .stdin("exit 3")
+            .term()
+            .fs()
+            .sandbox(&Sandbox::default())
+            .run()?;
+        assert_eq!(proc.state.status, Some(Status::Failed));
+        proc.io.clean().into_diagnostic()?;
+        Ok(())
+    }
+    #[test]
     fn background() -> Result<()> {
         let proc = Process::new().stdin("sleep 3").background().run()?;
         assert_eq!(proc.io.stdout, None);
@@ -175,4 +600,109 @@ mod test {
         assert_eq!(proc.io.stdout, Some("test\n".to_owned()));
         Ok(())
     }
+
+    /**
+     * Helper spawned by the detach tests, as the parent process.
+     * Detach a child and record its ids in a file, then wait to be killed.
+     */
+    #[test]
+    #[ignore = "spawned by the detach tests"]
+    fn detaching_parent() -> Result<()> {
+        let file = match std::env::var("PIPELIGHT_TEST_IDS_FILE") {
+            Ok(file) => file,
+            Err(_) => return Ok(()),
+        };
+        let mut proc = Process::new().stdin("sleep 30").background().to_owned();
+        match std::env::var("PIPELIGHT_TEST_DETACH").as_deref() {
+            Ok("hard") => proc.hard_detach(),
+            _ => proc.soft_detach(),
+        };
+        let proc = proc.run()?;
+        let ids = format!(
+            "{} {} {}",
+            proc.pid.unwrap(),
+            proc.pgid.unwrap(),
+            proc.sid.unwrap()
+        );
+        // Write atomically for the test to never read partial ids
+        std::fs::write(format!("{file}.tmp"), ids).into_diagnostic()?;
+        std::fs::rename(format!("{file}.tmp"), &file).into_diagnostic()?;
+        thread::sleep(time::Duration::from_secs(30));
+        Ok(())
+    }
+    /**
+     * Spawn a parent, leader of its own process group, that detaches a child.
+     * Returns the parent and the child pid, pgid and sid.
+     */
+    fn spawn_detaching_parent(mode: &str) -> Result<(Child, [i32; 3])> {
+        let file = std::env::temp_dir().join(format!("pipelight_detach_{}", uuid::Uuid::new_v4()));
+        let parent = Command::new(std::env::current_exe().into_diagnostic()?)
+            .args([
+                "--exact",
+                "process::run::test::detaching_parent",
+                "--ignored",
+            ])
+            .env("PIPELIGHT_TEST_DETACH", mode)
+            .env("PIPELIGHT_TEST_IDS_FILE", &file)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .into_diagnostic()?;
+        let start = time::Instant::now();
+        while !file.is_file() {
+            assert!(start.elapsed() < time::Duration::from_secs(10));
+            thread::sleep(time::Duration::from_millis(10));
+        }
+        let ids = std::fs::read_to_string(&file).into_diagnostic()?;
+        std::fs::remove_file(&file).into_diagnostic()?;
+        let ids: Vec<i32> = ids.split(' ').map(|e| e.parse().unwrap()).collect();
+        Ok((parent, [ids[0], ids[1], ids[2]]))
+    }
+    /**
+     * Whether a process is running (zombies are dead).
+     */
+    fn is_alive(pid: i32) -> bool {
+        // Let the signals be delivered
+        thread::sleep(time::Duration::from_millis(200));
+        match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+            Ok(stat) => !stat.rsplit_once(") ").unwrap().1.starts_with('Z'),
+            Err(_) => false,
+        }
+    }
+    #[test]
+    fn soft_detach() -> Result<()> {
+        let (mut parent, [pid, pgid, sid]) = spawn_detaching_parent("soft")?;
+        let parent_pid = Pid::from_raw(parent.id() as i32).unwrap();
+        // Keeps the parent process group and session
+        assert_eq!(pgid, parent.id() as i32);
+        assert_eq!(Some(sid), getsid(None).ok().map(|e| Pid::as_raw(Some(e))));
+
+        // Killing the parent doesn't kill the child
+        rustix::process::kill_process(parent_pid, Signal::Kill).into_diagnostic()?;
+        parent.wait().into_diagnostic()?;
+        assert!(is_alive(pid));
+
+        // Killing the parent group does
+        kill_process_group(parent_pid, Signal::Kill).into_diagnostic()?;
+        assert!(!is_alive(pid));
+        Ok(())
+    }
+    #[test]
+    fn hard_detach() -> Result<()> {
+        let (mut parent, [pid, pgid, sid]) = spawn_detaching_parent("hard")?;
+        let parent_pid = Pid::from_raw(parent.id() as i32).unwrap();
+        // Leads its own session and process group
+        assert_eq!(pgid, pid);
+        assert_eq!(sid, pid);
+
+        // Neither killing the parent group nor the parent kills the child
+        kill_process_group(parent_pid, Signal::Kill).into_diagnostic()?;
+        parent.wait().into_diagnostic()?;
+        assert!(is_alive(pid));
+
+        let pid = Pid::from_raw(pid).unwrap();
+        rustix::process::kill_process(pid, Signal::Kill).into_diagnostic()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::os::fd::BorrowedFd;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+// Unix process manipulation
+use rustix::fs::statvfs;
+use rustix::io::{fcntl_getfd, Errno};
+use rustix::mount::{
+    mount, mount_change, mount_recursive_bind, mount_remount, MountFlags, MountPropagationFlags,
+};
+use rustix::process::{
+    chdir, getgid, getrlimit, getuid, set_parent_process_death_signal, waitpid, Resource, Signal,
+    WaitOptions,
+};
+use rustix::runtime::{exit_group, fork, Fork};
+use rustix::thread::{unshare, UnshareFlags};
+
+// Error Handling
+use pipelight_error::LibError;
+
+/**
+* The writable directory of a sandboxed process.
+* A fresh tmpfs, dropped with the sandbox.
+*/
+pub static SCRATCH_DIR: &str = "/tmp";
+
+/**
+* Isolation of a process from the host, with Linux namespaces.
+*
+* The process runs in new user, mount and pid namespaces
+* (and a new network namespace unless the network is allowed):
+* - it is root of its own user namespace, mapped to the calling user,
+* - the project (current directory) and $HOME are mounted read-only,
+* - a fresh tmpfs is mounted on /tmp as a writable scratch directory,
+* - the given writable paths stay writable,
+* - it is pid 1 of its own pid namespace and can't see the host processes,
+* - without network, only a down loopback interface is available.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Sandbox {
+    pub network: bool,
+    pub writable: Vec<String>,
+}
+
+/**
+* Everything the forked child needs,
+* computed beforehand as no allocation should happen between fork and exec.
+*/
+struct Prepared {
+    flags: UnshareFlags,
+    uid_map: CString,
+    gid_map: CString,
+    read_only: Vec<CString>,
+    writable: Vec<CString>,
+    scratch: CString,
+    cwd: CString,
+}
+
+impl Sandbox {
+    /**
+     * Make the command spawn into the sandbox.
+     */
+    pub fn apply(&self, cmd: &mut Command) -> Result<(), LibError> {
+        let mut flags = UnshareFlags::NEWUSER | UnshareFlags::NEWNS | UnshareFlags::NEWPID;
+        if !self.network {
+            flags |= UnshareFlags::NEWNET;
+        }
+        let mut read_only = vec![];
+        if let Ok(home) = std::env::var("HOME") {
+            if std::path::Path::new(&home).is_dir() {
+                read_only.push(to_cstring(&home)?);
+            }
+        }
+        let root = std::env::current_dir().map_err(|err| LibError {
+            message: "Couldn't get the sandbox working directory".to_owned(),
+            help: err.to_string(),
+        })?;
+        read_only.push(to_cstring(&root.display().to_string())?);
+        let cwd = match cmd.get_current_dir() {
+            Some(dir) => root.join(dir),
+            None => root,
+        };
+        let prepared = Prepared {
+            flags,
+            uid_map: to_cstring(&format!("0 {} 1", getuid().as_raw()))?,
+            gid_map: to_cstring(&format!("0 {} 1", getgid().as_raw()))?,
+            read_only,
+            writable: self
+                .writable
+                .iter()
+                .map(|e| to_cstring(e))
+                .collect::<Result<Vec<CString>, LibError>>()?,
+            scratch: to_cstring(SCRATCH_DIR)?,
+            cwd: to_cstring(&cwd.display().to_string())?,
+        };
+        cmd.env("PIPELIGHT_SCRATCH", SCRATCH_DIR);
+        unsafe {
+            cmd.pre_exec(move || prepared.enter().map_err(std::io::Error::from));
+        }
+        Ok(())
+    }
+}
+
+impl Prepared {
+    /**
+     * Enter the namespaces and set the mounts up.
+     * Runs in the forked child, right before exec.
+     */
+    fn enter(&self) -> Result<(), Errno> {
+        unshare(self.flags)?;
+
+        // Be root of the user namespace
+        write_file(c"/proc/self/setgroups", b"deny")?;
+        write_file(c"/proc/self/uid_map", self.uid_map.as_bytes())?;
+        write_file(c"/proc/self/gid_map", self.gid_map.as_bytes())?;
+
+        // Keep the mounts from propagating to the host
+        mount_change(
+            c"/",
+            MountPropagationFlags::REC | MountPropagationFlags::PRIVATE,
+        )?;
+        for path in &self.read_only {
+            rebind(path, MountFlags::RDONLY)?;
+        }
+        for path in &self.writable {
+            rebind(path, MountFlags::empty())?;
+        }
+        mount(
+            c"tmpfs",
+            self.scratch.as_c_str(),
+            c"tmpfs",
+            MountFlags::NOSUID | MountFlags::NODEV,
+            c"",
+        )?;
+        // The working directory was entered before the mounts
+        chdir(self.cwd.as_c_str())?;
+
+        // Only the children of the caller enter the new pid namespace:
+        // fork and let the parent forward the child exit status.
+        match unsafe { fork()? } {
+            Fork::Child(_) => {
+                // Take the whole namespace down with the parent
+                set_parent_process_death_signal(Some(Signal::Kill))?;
+                // Show the sandbox processes only, when possible
+                mount(
+                    c"proc",
+                    c"/proc",
+                    c"proc",
+                    MountFlags::NOSUID | MountFlags::NODEV | MountFlags::NOEXEC,
+                    c"",
+                )
+                .ok();
+                Ok(())
+            }
+            Fork::Parent(pid) => {
+                // Release the pipes shared with the child,
+                // so that the caller only waits for the child.
+                let max = getrlimit(Resource::Nofile)
+                    .current
+                    .unwrap_or(1024)
+                    .min(65536);
+                for fd in 3..max as i32 {
+                    let borrowed = unsafe { BorrowedFd::borrow_raw(fd) };
+                    if fcntl_getfd(borrowed).is_ok() {
+                        unsafe { rustix::io::close(fd) };
+                    }
+                }
+                let code = match waitpid(Some(pid), WaitOptions::empty()) {
+                    Ok(Some(status)) => match status.terminating_signal() {
+                        Some(signal) => 128 + signal as i32,
+                        None => status.exit_status().unwrap_or(1) as i32,
+                    },
+                    _ => 1,
+                };
+                exit_group(code)
+            }
+        }
+    }
+}
+
+/**
+* Bind a path onto itself and change the new mount flags.
+*/
+fn rebind(path: &CString, flags: MountFlags) -> Result<(), Errno> {
+    mount_recursive_bind(path.as_c_str(), path.as_c_str())?;
+    // Flags inherited from the host mount can't be dropped
+    let locked = MountFlags::from_bits_truncate(statvfs(path.as_c_str())?.f_flag.bits() as u32)
+        & (MountFlags::NOSUID
+            | MountFlags::NODEV
+            | MountFlags::NOEXEC
+            | MountFlags::NOATIME
+            | MountFlags::NODIRATIME
+            | MountFlags::RELATIME);
+    mount_remount(path.as_c_str(), MountFlags::BIND | flags | locked, c"")
+}
+
+/**
+* Write a whole buffer to an existing file.
+*/
+pub(super) fn write_file(path: &std::ffi::CStr, content: &[u8]) -> Result<(), Errno> {
+    let fd = rustix::fs::open(path, rustix::fs::OFlags::WRONLY, rustix::fs::Mode::empty())?;
+    rustix::io::write(&fd, content)?;
+    Ok(())
+}
+
+fn to_cstring(e: &str) -> Result<CString, LibError> {
+    CString::new(e).map_err(|err| LibError {
+        message: format!("Invalid sandbox path {:?}", e),
+        help: err.to_string(),
+    })
+}
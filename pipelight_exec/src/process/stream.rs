@@ -0,0 +1,273 @@
+use crate::{redact, Process};
+
+// Globals
+use crate::globals::OUTDIR;
+
+// Unix process manipulation
+use rustix::process::{kill_process_group, Pid, Signal};
+use std::process::{Child, ExitStatus, Output};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time;
+
+// File manipulation
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+
+/**
+* The standard output a line has been printed on.
+*/
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/**
+* A line of a process output, without its trailing newline.
+*/
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Line {
+    pub stream: Stream,
+    pub text: String,
+}
+
+impl Process {
+    /**
+     * Wait for the child process to exit while forwarding its output lines.
+     * Kill the child process group if it runs longer than the timeout.
+     *
+     * Once the child has exited, the lines left in the pipes are still forwarded,
+     * but the pipes aren't waited for: they may be held open by a backgrounded child.
+     *
+     * Returns the process output and whether it has timed out.
+     */
+    pub(super) fn wait_streamed(
+        &self,
+        mut child: Child,
+        on_line: &mut dyn FnMut(&Line),
+    ) -> std::io::Result<(Output, bool)> {
+        let (mut stdout_file, mut stderr_file) = (None, None);
+        if self.config.fs {
+            let proc_path = format!("{}/{}", *OUTDIR.lock().unwrap(), self.uuid);
+            stdout_file = Some(File::create(format!("{proc_path}/1"))?);
+            stderr_file = Some(File::create(format!("{proc_path}/2"))?);
+        }
+
+        // Lines are sent back to the calling thread,
+        // so that the callback needs not be shared across threads.
+        let (sender, receiver) = channel::<Line>();
+        let secrets = &self.config.secrets;
+        let (stdout, stderr) = (Arc::default(), Arc::default());
+        if let Some(pipe) = child.stdout.take() {
+            tee(
+                pipe,
+                Stream::Stdout,
+                stdout_file,
+                secrets.clone(),
+                sender.clone(),
+                Arc::clone(&stdout),
+            );
+        }
+        if let Some(pipe) = child.stderr.take() {
+            tee(
+                pipe,
+                Stream::Stderr,
+                stderr_file,
+                secrets.clone(),
+                sender.clone(),
+                Arc::clone(&stderr),
+            );
+        }
+        drop(sender);
+
+        let start = time::Instant::now();
+        let throttle = time::Duration::from_millis(10);
+        let mut timed_out = false;
+        // Until the child exits
+        let status = loop {
+            match receiver.recv_timeout(throttle) {
+                Ok(line) => on_line(&line),
+                Err(RecvTimeoutError::Timeout) => {}
+                // Every pipe is closed
+                Err(RecvTimeoutError::Disconnected) => thread::sleep(throttle),
+            }
+            if let Some(status) = self.poll(&mut child, &start, &mut timed_out)? {
+                break status;
+            }
+        };
+        // Until the pipes are drained, or stay silent
+        let idle = time::Duration::from_millis(50);
+        let deadline = time::Instant::now() + time::Duration::from_secs(1);
+        while time::Instant::now() < deadline {
+            match receiver.recv_timeout(idle) {
+                Ok(line) => on_line(&line),
+                Err(_) => break,
+            }
+        }
+
+        let output = Output {
+            status,
+            stdout: stdout.lock().unwrap().clone(),
+            stderr: stderr.lock().unwrap().clone(),
+        };
+        Ok((output, timed_out))
+    }
+    /**
+     * Check whether the child has exited,
+     * and kill its process group once the timeout is reached
+     * or a cancellation flag is raised.
+     */
+    pub(super) fn poll(
+        &self,
+        child: &mut Child,
+        start: &time::Instant,
+        timed_out: &mut bool,
+    ) -> std::io::Result<Option<ExitStatus>> {
+        let status = child.try_wait()?;
+        if status.is_some() {
+            return Ok(status);
+        }
+        let pgid = Pid::from_raw(child.id() as i32).unwrap();
+        if let Some(timeout) = self.config.timeout {
+            if !*timed_out && start.elapsed() >= timeout {
+                *timed_out = true;
+                kill_process_group(pgid, Signal::Kill).ok();
+            }
+        }
+        if self.config.cancel.iter().any(|e| e.is_raised()) {
+            kill_process_group(pgid, Signal::Kill).ok();
+        }
+        Ok(status)
+    }
+}
+
+/**
+* Read a pipe line by line in a dedicated thread:
+* send every masked line, write it to the file if any,
+* and append the raw line to the shared buffer.
+* The thread ends once the pipe is closed, or on a write error.
+*/
+fn tee(
+    pipe: impl Read + Send + 'static,
+    stream: Stream,
+    mut file: Option<File>,
+    secrets: Vec<String>,
+    sender: Sender<Line>,
+    buf: Arc<Mutex<Vec<u8>>>,
+) {
+    thread::spawn(move || -> std::io::Result<()> {
+        let mut reader = BufReader::new(pipe);
+        let mut line = vec![];
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            buf.lock().unwrap().extend_from_slice(&line);
+            let text = redact(&String::from_utf8_lossy(&line), &secrets);
+            if let Some(file) = &mut file {
+                file.write_all(text.as_bytes())?;
+            }
+            let line = Line {
+                stream: stream.clone(),
+                text: text.trim_end_matches(['\n', '\r']).to_owned(),
+            };
+            // The receiver is gone once the child has exited
+            sender.send(line).ok();
+        }
+        Ok(())
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Status;
+    use miette::{IntoDiagnostic, Result};
+
+    #[test]
+    fn stream_lines() -> Result<()> {
+        let mut lines = vec![];
+        let proc = Process::new()
+            .stdin("echo one; echo two >&2; echo three")
+            .term()
+            .stream(|line| lines.push(line.to_owned()))?;
+        let stdout: Vec<&str> = lines
+            .iter()
+            .filter(|e| e.stream == Stream::Stdout)
+            .map(|e| e.text.as_str())
+            .collect();
+        let stderr: Vec<&str> = lines
+            .iter()
+            .filter(|e| e.stream == Stream::Stderr)
+            .map(|e| e.text.as_str())
+            .collect();
+        assert_eq!(stdout, vec!["one", "three"]);
+        assert_eq!(stderr, vec!["two"]);
+        assert_eq!(proc.io.stdout, Some("one\nthree\n".to_owned()));
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn stream_live() -> Result<()> {
+        let start = time::Instant::now();
+        let mut arrivals = vec![];
+        Process::new()
+            .stdin("echo first; sleep 1; echo second")
+            .term()
+            .stream(|_| arrivals.push(start.elapsed()))?;
+        // The first line is received before the process ends
+        assert_eq!(arrivals.len(), 2);
+        assert!(arrivals[0] < time::Duration::from_millis(800));
+        assert!(arrivals[1] >= time::Duration::from_secs(1));
+        Ok(())
+    }
+    #[test]
+    fn stream_fs_secrets() -> Result<()> {
+        let secrets = vec!["s3cr3t".to_owned()];
+        let mut lines = vec![];
+        let mut proc = Process::new()
+            .stdin("echo token=s3cr3t")
+            .term()
+            .fs()
+            .secrets(&secrets)
+            .stream(|line| lines.push(line.text.to_owned()))?;
+        assert_eq!(lines, vec!["token=***"]);
+        assert_eq!(proc.io.stdout, Some("token=***\n".to_owned()));
+        // Teed into the process files
+        proc.io.stdout = None;
+        proc.io.read().into_diagnostic()?;
+        assert_eq!(proc.io.stdout, Some("token=***\n".to_owned()));
+        proc.io.clean().into_diagnostic()?;
+        Ok(())
+    }
+    #[test]
+    fn stream_background_child() -> Result<()> {
+        let start = time::Instant::now();
+        let mut lines = vec![];
+        // The backgrounded child holds the pipes open after the shell exits
+        let proc = Process::new()
+            .stdin("sleep 4 & echo started")
+            .term()
+            .stream(|line| lines.push(line.text.to_owned()))?;
+        assert!(start.elapsed() < time::Duration::from_secs(2));
+        assert_eq!(lines, vec!["started"]);
+        assert_eq!(proc.io.stdout, Some("started\n".to_owned()));
+        assert_eq!(proc.state.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn stream_timeout() -> Result<()> {
+        let mut lines = vec![];
+        let proc = Process::new()
+            .stdin("echo start; sleep 10")
+            .term()
+            .timeout(time::Duration::from_millis(500))
+            .stream(|line| lines.push(line.text.to_owned()))?;
+        assert_eq!(lines, vec!["start"]);
+        assert_eq!(proc.state.status, Some(Status::TimedOut));
+        Ok(())
+    }
+}
@@ -20,6 +20,24 @@ use log::warn;
 pub struct State {
     pub duration: Option<Duration>,
     pub status: Option<Status>,
+    // Why the process has failed, when known.
+    pub reason: Option<Reason>,
+}
+
+/**
+* A known cause of a process failure.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "kebab-case")]
+pub enum Reason {
+    // The process has run out of memory.
+    MemoryLimit,
+    // The process has used up its CPU time.
+    CpuTimeLimit,
+    // The process couldn't open more files.
+    NofileLimit,
+    // The process couldn't spawn more processes.
+    NprocLimit,
 }
 
 /**
@@ -39,17 +57,24 @@ pub enum Status {
     Running,
     // The process has been abruptly halted.
     Aborted,
+    // The process has been killed for running longer than its timeout.
+    TimedOut,
+    // The process has not been run because its condition was not met.
+    Skipped,
 }
 
 impl From<&String> for Status {
     fn from(status: &String) -> Status {
-        let cased: &str = &status.to_case(Case::Snake);
+        let cased: &str = &status.to_case(Case::Kebab);
         match cased {
             "started" => Status::Started,
             "succeeded" => Status::Succeeded,
             "failed" => Status::Failed,
             "running" => Status::Running,
             "aborted" => Status::Aborted,
+            // Same as serialized, and the snake case alias
+            "timed-out" | "timed_out" => Status::TimedOut,
+            "skipped" => Status::Skipped,
             _ => {
                 warn!("unexpected string, assuminng default state");
                 Status::default()
@@ -65,6 +90,8 @@ impl From<&Status> for String {
             Status::Failed => "failed".to_owned(),
             Status::Running => "running".to_owned(),
             Status::Aborted => "aborted".to_owned(),
+            Status::TimedOut => "timed-out".to_owned(),
+            Status::Skipped => "skipped".to_owned(),
         }
     }
 }
@@ -77,6 +104,16 @@ impl From<&Output> for Status {
         }
     }
 }
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Reason::MemoryLimit => write!(f, "memory limit exceeded"),
+            Reason::CpuTimeLimit => write!(f, "cpu time limit exceeded"),
+            Reason::NofileLimit => write!(f, "open files limit exceeded"),
+            Reason::NprocLimit => write!(f, "processes limit exceeded"),
+        }
+    }
+}
 /**
 * Displays a nice and colorful string for easy status checks.
 */
@@ -89,6 +126,33 @@ impl fmt::Display for Status {
             Status::Failed => write!(f, "{} {}", icon.red(), "Failed".normal().bold()),
             Status::Running => write!(f, "{} {}", icon.green(), "Running".bold()),
             Status::Aborted => write!(f, "{} {}", icon.yellow(), "Aborted".bold()),
+            Status::TimedOut => write!(f, "{} {}", icon.magenta(), "Timed out".bold()),
+            Status::Skipped => write!(f, "{} {}", icon.white(), "Skipped".bold()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Status;
+
+    #[test]
+    fn status_round_trip() {
+        let statuses = [
+            Status::Started,
+            Status::Succeeded,
+            Status::Failed,
+            Status::Running,
+            Status::Aborted,
+            Status::TimedOut,
+            Status::Skipped,
+        ];
+        for status in statuses {
+            let string = String::from(&status);
+            assert_eq!(Status::from(&string), status);
+            // Same as serialized
+            assert_eq!(serde_plain::to_string(&status).unwrap(), string);
         }
+        assert_eq!(Status::from(&"timed_out".to_owned()), Status::TimedOut);
     }
 }
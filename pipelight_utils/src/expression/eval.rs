@@ -0,0 +1,61 @@
+// Structs
+use super::types::{Expression, Value};
+
+impl Value {
+    /**
+    Null, false and empty strings are falsy.
+    */
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Str(e) => !e.is_empty(),
+            Value::Bool(e) => *e,
+            Value::Null => false,
+        }
+    }
+}
+
+impl Expression {
+    /**
+    Compute the expression value.
+    The lookup function resolves variables (dotted paths) to their values.
+    */
+    pub fn value(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Value {
+        match self {
+            Expression::Literal(e) => Value::Str(e.to_owned()),
+            Expression::Bool(e) => Value::Bool(*e),
+            Expression::Variable(path) => match lookup(path) {
+                Some(e) => Value::Str(e),
+                None => Value::Null,
+            },
+            Expression::Not(e) => Value::Bool(!e.value(lookup).is_truthy()),
+            Expression::And(left, right) => {
+                Value::Bool(left.value(lookup).is_truthy() && right.value(lookup).is_truthy())
+            }
+            Expression::Or(left, right) => {
+                Value::Bool(left.value(lookup).is_truthy() || right.value(lookup).is_truthy())
+            }
+            Expression::Equal(left, right) => {
+                Value::Bool(equals(&left.value(lookup), &right.value(lookup)))
+            }
+            Expression::NotEqual(left, right) => {
+                Value::Bool(!equals(&left.value(lookup), &right.value(lookup)))
+            }
+        }
+    }
+    /**
+    Evaluate the expression as a condition.
+    */
+    pub fn eval(&self, lookup: &dyn Fn(&str) -> Option<String>) -> bool {
+        self.value(lookup).is_truthy()
+    }
+}
+
+/**
+Variables are strings, so booleans compare to their string representation.
+*/
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Bool(a), Value::Str(b)) | (Value::Str(b), Value::Bool(a)) => &a.to_string() == b,
+        (a, b) => a == b,
+    }
+}
@@ -0,0 +1,54 @@
+/**
+Replace every `${{ path }}` placeholder of a template with its value.
+Placeholders the lookup function can't resolve are left untouched,
+so that they can be resolved later on.
+
+```rust
+# use pipelight_utils::expression::interpolate;
+let lookup = |path: &str| match path {
+    "matrix.toolchain" => Some("stable".to_owned()),
+    _ => None,
+};
+let res = interpolate("cargo +${{ matrix.toolchain }} test ${{ matrix.features }}", &lookup);
+assert_eq!(res, "cargo +stable test ${{ matrix.features }}");
+```
+*/
+pub fn interpolate(template: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    interpolate_at(template, &|path: &str, _: &str| lookup(path))
+}
+
+/**
+Replace every `${{ path }}` placeholder of a template with its value,
+the lookup function also gets the template text preceding the placeholder
+(ex: to quote the value for where it stands).
+
+```rust
+# use pipelight_utils::expression::interpolate_at;
+let lookup = |path: &str, before: &str| match before.ends_with('+') {
+    true => Some(path.to_owned()),
+    false => None,
+};
+let res = interpolate_at("cargo +${{ stable }} test ${{ all }}", &lookup);
+assert_eq!(res, "cargo +stable test ${{ all }}");
+```
+*/
+pub fn interpolate_at(template: &str, lookup: &dyn Fn(&str, &str) -> Option<String>) -> String {
+    let mut res = "".to_owned();
+    let mut rest = template;
+    while let Some(start) = rest.find("${{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + len + 2];
+        let path = placeholder[3..placeholder.len() - 2].trim();
+        res.push_str(&rest[..start]);
+        let before = &template[..template.len() - rest.len() + start];
+        match lookup(path, before) {
+            Some(value) => res.push_str(&value),
+            None => res.push_str(placeholder),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    res.push_str(rest);
+    res
+}
@@ -0,0 +1,11 @@
+mod eval;
+mod interpolate;
+mod parse;
+mod types;
+
+// Tests
+mod test;
+
+// Re-export
+pub use interpolate::{interpolate, interpolate_at};
+pub use types::*;
@@ -0,0 +1,179 @@
+// Structs
+use super::types::Expression;
+use std::str::FromStr;
+// Error Handling
+use miette::{Error, Result};
+
+/**
+Lexical units of an expression.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(String),
+    Ident(String),
+    And,
+    Or,
+    Not,
+    Equal,
+    NotEqual,
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens: Vec<Token> = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {}
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '\'' | '"' => {
+                let mut literal = "".to_owned();
+                loop {
+                    match chars.next() {
+                        Some(e) if e == c => break,
+                        Some(e) => literal.push(e),
+                        None => {
+                            let message = format!("Unterminated string in expression: {}", input);
+                            return Err(Error::msg(message));
+                        }
+                    }
+                }
+                tokens.push(Token::Literal(literal));
+            }
+            '&' | '|' | '=' => {
+                if chars.next() != Some(c) {
+                    let message = format!("Expected '{c}{c}' in expression: {}", input);
+                    return Err(Error::msg(message));
+                }
+                tokens.push(match c {
+                    '&' => Token::And,
+                    '|' => Token::Or,
+                    _ => Token::Equal,
+                });
+            }
+            '!' => {
+                if chars.peek() == Some(&'=') {
+                    chars.next();
+                    tokens.push(Token::NotEqual);
+                } else {
+                    tokens.push(Token::Not);
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(e) = chars
+                    .peek()
+                    .filter(|e| e.is_alphanumeric() || ['_', '-', '.'].contains(e))
+                {
+                    ident.push(*e);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => {
+                let message = format!("Unexpected character '{}' in expression: {}", c, input);
+                return Err(Error::msg(message));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/**
+Recursive descent parser.
+Precedence from lowest to highest: ||, &&, !, ==/!=.
+*/
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+    fn or(&mut self) -> Result<Expression> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expression::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+    fn and(&mut self) -> Result<Expression> {
+        let mut left = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Expression::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+    fn not(&mut self) -> Result<Expression> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expression::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+    fn comparison(&mut self) -> Result<Expression> {
+        let left = self.primary()?;
+        match self.peek() {
+            Some(Token::Equal) => {
+                self.next();
+                Ok(Expression::Equal(Box::new(left), Box::new(self.primary()?)))
+            }
+            Some(Token::NotEqual) => {
+                self.next();
+                Ok(Expression::NotEqual(
+                    Box::new(left),
+                    Box::new(self.primary()?),
+                ))
+            }
+            _ => Ok(left),
+        }
+    }
+    fn primary(&mut self) -> Result<Expression> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expression = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expression),
+                    _ => Err(Error::msg("Expected ')' in expression")),
+                }
+            }
+            Some(Token::Literal(literal)) => Ok(Expression::Literal(literal)),
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Ok(Expression::Bool(true)),
+                "false" => Ok(Expression::Bool(false)),
+                _ => Ok(Expression::Variable(ident)),
+            },
+            Some(token) => Err(Error::msg(format!(
+                "Unexpected token {:?} in expression",
+                token
+            ))),
+            None => Err(Error::msg("Unexpected end of expression")),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+        let expression = parser.or()?;
+        if let Some(token) = parser.peek() {
+            let message = format!("Unexpected token {:?} in expression: {}", token, s);
+            return Err(Error::msg(message));
+        }
+        Ok(expression)
+    }
+}
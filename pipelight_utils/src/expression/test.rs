@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod try_expression {
+    use crate::expression::Expression;
+    use std::collections::HashMap;
+
+    // Error Handling
+    use miette::Result;
+
+    fn context() -> HashMap<String, String> {
+        HashMap::from([
+            ("branch".to_owned(), "main".to_owned()),
+            ("action".to_owned(), "pre-push".to_owned()),
+            ("steps.build.status".to_owned(), "succeeded".to_owned()),
+        ])
+    }
+
+    #[test]
+    fn eval_comparisons() -> Result<()> {
+        let context = context();
+        let lookup = |path: &str| context.get(path).cloned();
+
+        let expression: Expression = "branch == 'main' && action == \"pre-push\"".parse()?;
+        assert!(expression.eval(&lookup));
+
+        let expression: Expression =
+            "branch != 'main' || steps.build.status == 'failed'".parse()?;
+        assert!(!expression.eval(&lookup));
+        Ok(())
+    }
+    #[test]
+    fn eval_precedence() -> Result<()> {
+        let context = context();
+        let lookup = |path: &str| context.get(path).cloned();
+
+        let expression: Expression = "!(branch == 'dev') && (tag || true)".parse()?;
+        assert!(expression.eval(&lookup));
+
+        // Unknown variables are falsy
+        let expression: Expression = "env.UNSET".parse()?;
+        assert!(!expression.eval(&lookup));
+        Ok(())
+    }
+    #[test]
+    fn parse_errors() -> Result<()> {
+        assert!("branch = 'main'".parse::<Expression>().is_err());
+        assert!("branch == 'main".parse::<Expression>().is_err());
+        assert!("(branch == 'main'".parse::<Expression>().is_err());
+        assert!("branch == 'main' tag".parse::<Expression>().is_err());
+        Ok(())
+    }
+}
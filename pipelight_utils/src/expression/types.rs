@@ -0,0 +1,35 @@
+/**
+A parsed boolean expression, as used in step conditions.
+
+```text
+branch == 'main' && (action == 'pre-push' || env.CI)
+!(steps.build.status == 'failed')
+```
+
+Variables are dotted paths resolved at evaluation time.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    // A quoted string: 'main' or "main"
+    Literal(String),
+    // true or false
+    Bool(bool),
+    // A dotted path (ex: branch, env.HOME, steps.build.status)
+    Variable(String),
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Equal(Box<Expression>, Box<Expression>),
+    NotEqual(Box<Expression>, Box<Expression>),
+}
+
+/**
+The value an expression evaluates to.
+Unknown variables evaluate to Null.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    Null,
+}
@@ -53,6 +53,7 @@ pub mod globals;
 pub mod file;
 pub use file::FileType;
 
+pub mod expression;
 pub mod git;
 pub mod logger;
 pub mod signal;
@@ -1,3 +1,13 @@
+// Every command runs with the following environment variables:
+//   PIPELIGHT_PIPELINE      the pipeline name
+//   PIPELIGHT_RUN_UUID      the pipeline run unique id
+//   PIPELIGHT_STEP          the step name
+//   PIPELIGHT_BRANCH        the git branch (empty if none)
+//   PIPELIGHT_TAG           the git tag (empty if none)
+//   PIPELIGHT_COMMIT        the git commit (empty if none)
+//   PIPELIGHT_ACTION        the triggering action (ex: pre-push, manual)
+//   PIPELIGHT_PROJECT_ROOT  the config file directory
+
 const my_pipe = {
   name: "example",
   steps: [
@@ -7,7 +17,7 @@ const my_pipe = {
     },
     {
       name: "second",
-      commands: ["ls", "pwd"],
+      commands: ["ls", "pwd", "echo $PIPELIGHT_PIPELINE/$PIPELIGHT_STEP"],
     },
   ],
 };
@@ -1,5 +1,15 @@
 import type { Pipeline, Config } from "https://deno.land/x/pipelight/mod.ts";
 
+// Every command runs with the following environment variables:
+//   PIPELIGHT_PIPELINE      the pipeline name
+//   PIPELIGHT_RUN_UUID      the pipeline run unique id
+//   PIPELIGHT_STEP          the step name
+//   PIPELIGHT_BRANCH        the git branch (empty if none)
+//   PIPELIGHT_TAG           the git tag (empty if none)
+//   PIPELIGHT_COMMIT        the git commit (empty if none)
+//   PIPELIGHT_ACTION        the triggering action (ex: pre-push, manual)
+//   PIPELIGHT_PROJECT_ROOT  the config file directory
+
 const my_pipe: Pipeline = {
   name: "example",
   steps: [
@@ -9,7 +19,7 @@ const my_pipe: Pipeline = {
     },
     {
       name: "second",
-      commands: ["ls", "pwd"],
+      commands: ["ls", "pwd", "echo $PIPELIGHT_PIPELINE/$PIPELIGHT_STEP"],
     },
   ],
 };
@@ -1,3 +1,4 @@
+mod secrets;
 // Globals
 use crate::globals::CONFIG;
 // Error Handling
@@ -84,6 +85,12 @@ impl Config {
             Ok(false)
         }
     }
+    /**
+     Return the project-wide maximum number of running steps, if any.
+    */
+    pub fn get_max_jobs(&self) -> Option<usize> {
+        self.options.as_ref().and_then(|e| e.max_jobs)
+    }
     pub fn get_default_loglevel(&self) -> Result<LevelFilter> {
         if let Some(options) = &self.options {
             if let Some(log_level) = options.log_level {
@@ -0,0 +1,102 @@
+// Globals
+use crate::globals::SECRETS;
+// Structs
+use crate::types::{Config, Secret};
+use std::collections::BTreeMap;
+// Filesystem
+use std::fs;
+// Error Handling
+use log::warn;
+use miette::{Error, IntoDiagnostic, Result};
+
+impl Config {
+    /**
+    Read the secret values from their sources.
+    A secret that can't be read is left out with a warning,
+    so that commands that don't need it still run.
+    */
+    pub fn load_secrets(&self) {
+        let mut secrets = BTreeMap::new();
+        for (name, secret) in self.secrets.iter().flatten() {
+            match secret.read() {
+                Ok(value) => {
+                    secrets.insert(name.clone(), value);
+                }
+                Err(err) => warn!("Couldn't read secret {}: {}", name, err),
+            }
+        }
+        *SECRETS.lock().unwrap() = secrets;
+    }
+}
+
+impl Secret {
+    /**
+    Read the secret value from its environment variable or file.
+    Trailing newlines of files are ignored.
+    */
+    pub fn read(&self) -> Result<String> {
+        if let Some(env) = &self.env {
+            return std::env::var(env).into_diagnostic();
+        }
+        if let Some(file) = &self.file {
+            let value = fs::read_to_string(file).into_diagnostic()?;
+            return Ok(value.trim_end_matches(['\n', '\r']).to_owned());
+        }
+        Err(Error::msg("The secret has no source"))
+    }
+    /**
+    Return the loaded secret names and values.
+    */
+    pub fn get_all() -> BTreeMap<String, String> {
+        SECRETS.lock().unwrap().clone()
+    }
+    /**
+    Return the loaded secret values to be masked.
+    The lines of multiline values are masked on their own too.
+    */
+    pub fn get_values() -> Vec<String> {
+        let mut values = vec![];
+        for value in SECRETS.lock().unwrap().values() {
+            values.push(value.clone());
+            if value.contains('\n') {
+                values.extend(
+                    value
+                        .lines()
+                        .map(|e| e.trim().to_owned())
+                        .filter(|e| !e.is_empty()),
+                );
+            }
+        }
+        values
+    }
+    /**
+    Mask the loaded secret values in a text,
+    including their JSON escaped form.
+    */
+    pub fn redact(text: &str) -> String {
+        let mut values = Secret::get_values();
+        let escaped: Vec<String> = values
+            .iter()
+            .filter_map(|e| serde_json::to_string(e).ok())
+            .map(|e| e[1..e.len() - 1].to_owned())
+            .collect();
+        values.extend(escaped);
+        pipelight_exec::redact(text, &values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::globals::SECRETS;
+    use crate::types::Secret;
+
+    #[test]
+    fn redact() {
+        SECRETS.lock().unwrap().extend([
+            ("TEST_TOKEN".to_owned(), "s3cr3t".to_owned()),
+            ("TEST_KEY".to_owned(), "multi\"line\nkey".to_owned()),
+        ]);
+        let json = serde_json::to_string(&vec!["curl -H s3cr3t", "multi\"line\nkey"]).unwrap();
+        assert_eq!(Secret::redact(&json), r#"["curl -H ***","***"]"#);
+    }
+}
@@ -1,5 +1,6 @@
 // Global vars
 use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 // Struct
 use crate::types::{Config, Pipeline, Trigger};
@@ -13,3 +14,12 @@ pub static CONFIG: Lazy<Arc<Mutex<Config>>> = Lazy::new(|| Arc::new(Mutex::new(C
 pub static TRIGGER_ENV: Lazy<Arc<Mutex<Trigger>>> =
     Lazy::new(|| Arc::new(Mutex::new(Trigger::default())));
 pub static LOGS: Lazy<Arc<Mutex<Option<Vec<Pipeline>>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+/**
+Whether the commands output lines are printed as they arrive (attached runs).
+*/
+pub static LIVE_OUTPUT: Lazy<Arc<Mutex<bool>>> = Lazy::new(|| Arc::new(Mutex::new(false)));
+/**
+Secret names and values, loaded once from their sources.
+*/
+pub static SECRETS: Lazy<Arc<Mutex<BTreeMap<String, String>>>> =
+    Lazy::new(|| Arc::new(Mutex::new(BTreeMap::new())));
@@ -9,6 +9,7 @@ mod config;
 pub mod globals;
 mod logs;
 pub mod pipeline;
+pub mod scheduler;
 mod step;
 mod trigger;
 
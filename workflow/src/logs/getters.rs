@@ -3,6 +3,7 @@ use crate::pipeline::Filters;
 use crate::types::{Logs, Pipeline};
 // Trait
 use crate::traits::Getters;
+use pipelight_exec::Status;
 // Error Handling
 use miette::{Error, IntoDiagnostic, Result};
 // Global vars
@@ -63,6 +64,34 @@ impl Getters<Pipeline> for Logs {
 
 // More getters
 impl Logs {
+    pub fn get_by_uuid(uuid: &str) -> Result<Pipeline> {
+        let pipelines = Logs::get()?;
+        match pipelines.into_iter().find(|p| p.uuid.to_string() == uuid) {
+            None => {
+                let message = format!("Couldn't find a pipeline run {:?}, in logs", uuid);
+                Err(Error::msg(message))
+            }
+            Some(p) => Ok(p),
+        }
+    }
+    /**
+    Get the most recent run of a pipeline that is still running.
+    */
+    pub fn get_running_by_name(name: &str) -> Result<Pipeline> {
+        Logs::refresh();
+        let pipelines = Logs::get_many_by_name(name)?;
+        for pipeline in pipelines.into_iter().rev() {
+            if matches!(
+                pipeline.status,
+                Some(Status::Started) | Some(Status::Running)
+            ) && pipeline.is_running()?
+            {
+                return Ok(pipeline);
+            }
+        }
+        let message = format!("Couldn't find a running pipeline named {:?}, in logs", name);
+        Err(Error::msg(message))
+    }
     pub fn get_many_by_name(name: &str) -> Result<Vec<Pipeline>> {
         let mut pipelines = Logs::get()?;
         pipelines = pipelines
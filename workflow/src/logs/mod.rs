@@ -7,8 +7,6 @@ mod getters;
 mod test;
 // Traits
 use pipelight_exec::{Statuable, Status};
-// IterMut
-use rayon::prelude::*;
 // Error Handling
 use miette::Result;
 // Global vars
@@ -20,7 +18,7 @@ impl Logs {
     */
     pub fn sanitize(&mut self) -> Result<Self> {
         if let Some(mut pipelines) = self.pipelines.clone() {
-            pipelines.par_iter_mut().for_each(|pipeline| {
+            pipelines.iter_mut().for_each(|pipeline| {
                 if pipeline.get_status() == Some(Status::Running) && !pipeline.is_running().unwrap()
                 {
                     pipeline.set_status(Some(Status::Aborted));
@@ -33,6 +31,13 @@ impl Logs {
         Ok(self.to_owned())
     }
     /**
+    Drop the logs read so far,
+    so that the next read reflects the runs of other processes.
+    */
+    pub fn refresh() {
+        *LOGS.lock().unwrap() = None;
+    }
+    /**
     Delete every logs but the ones from running pipelines
     */
     pub fn clean() -> Result<()> {
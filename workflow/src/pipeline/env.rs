@@ -0,0 +1,143 @@
+// Structs
+use crate::types::{Fallback, Pipeline, PipelineOpts, Step, StepOpts, StepOrParallel};
+use std::collections::BTreeMap;
+
+/**
+Environment variables inheritance.
+Config variables are overridden by pipeline variables,
+which are overridden by step variables.
+*/
+impl Pipeline {
+    pub fn get_env(&self) -> BTreeMap<String, String> {
+        self.options
+            .as_ref()
+            .and_then(|e| e.env.clone())
+            .unwrap_or_default()
+    }
+    /**
+    Merge the parent environment under the pipeline one
+    and propagate the result to every step, fallbacks included.
+    */
+    pub fn inherit_env(&mut self, parent: &BTreeMap<String, String>) {
+        let mut env = parent.clone();
+        env.extend(self.get_env());
+        if !env.is_empty() {
+            self.options.get_or_insert(PipelineOpts::default()).env = Some(env.clone());
+        }
+        for step in &mut self.steps {
+            step.inherit_env(&env);
+        }
+        if let Some(fallback) = &mut self.fallback {
+            fallback.inherit_env(&env);
+        }
+    }
+}
+impl Pipeline {
+    /**
+    Return the run metadata exported to every command of the given step.
+    Unknown values (ex: no tag on a branch) are exported as empty strings
+    so that the set of variables stays the same from one run to another.
+    */
+    pub fn get_metadata_env(&self, step: &str) -> BTreeMap<String, String> {
+        let project_root = std::env::current_dir()
+            .map(|e| e.display().to_string())
+            .unwrap_or_default();
+        let variable = |path: &str| self.get_variable(path).unwrap_or_default();
+        BTreeMap::from([
+            ("PIPELIGHT_PIPELINE".to_owned(), self.name.clone()),
+            ("PIPELIGHT_RUN_UUID".to_owned(), self.uuid.to_string()),
+            ("PIPELIGHT_STEP".to_owned(), step.to_owned()),
+            ("PIPELIGHT_BRANCH".to_owned(), variable("branch")),
+            ("PIPELIGHT_TAG".to_owned(), variable("tag")),
+            ("PIPELIGHT_COMMIT".to_owned(), variable("commit")),
+            ("PIPELIGHT_ACTION".to_owned(), variable("action")),
+            ("PIPELIGHT_PROJECT_ROOT".to_owned(), project_root),
+        ])
+    }
+}
+impl Fallback {
+    fn inherit_env(&mut self, parent: &BTreeMap<String, String>) {
+        for steps in [
+            &mut self.on_started,
+            &mut self.on_failure,
+            &mut self.on_success,
+            &mut self.on_abortion,
+            &mut self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for step in steps {
+                step.inherit_env(parent);
+            }
+        }
+    }
+}
+impl StepOrParallel {
+    fn inherit_env(&mut self, parent: &BTreeMap<String, String>) {
+        match self {
+            StepOrParallel::Step(step) => step.inherit_env(parent),
+            StepOrParallel::Parallel(parallel) => {
+                for step in &mut parallel.steps {
+                    step.inherit_env(parent);
+                }
+                if let Some(fallback) = &mut parallel.fallback {
+                    fallback.inherit_env(parent);
+                }
+            }
+        }
+    }
+}
+impl Step {
+    pub fn get_env(&self) -> BTreeMap<String, String> {
+        self.options
+            .as_ref()
+            .and_then(|e| e.env.clone())
+            .unwrap_or_default()
+    }
+    fn inherit_env(&mut self, parent: &BTreeMap<String, String>) {
+        let mut env = parent.clone();
+        env.extend(self.get_env());
+        if !env.is_empty() {
+            self.options.get_or_insert(StepOpts::default()).env = Some(env.clone());
+        }
+        if let Some(fallback) = &mut self.fallback {
+            fallback.inherit_env(&env);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Config, StepOrParallel};
+
+    #[test]
+    fn inheritance() {
+        let json = r#"
+          {
+            "options": { "env": { "LEVEL": "config", "CONFIG": "1" } },
+            "pipelines": [{
+              "name": "env",
+              "options": { "env": { "LEVEL": "pipeline", "PIPELINE": "1" } },
+              "steps": [
+                { "name": "build", "commands": ["ls"], "options": { "env": { "LEVEL": "step" } } }
+              ]
+            }]
+          }
+        "#;
+        let config = serde_json::from_str::<cast::Config>(json).unwrap();
+        let config = Config::from(&config);
+        let pipeline = &config.pipelines.unwrap()[0];
+        let StepOrParallel::Step(step) = &pipeline.steps[0] else {
+            panic!("expected a step");
+        };
+        let env = step.get_env();
+        assert_eq!(env.get("LEVEL"), Some(&"step".to_owned()));
+        assert_eq!(env.get("PIPELINE"), Some(&"1".to_owned()));
+        assert_eq!(env.get("CONFIG"), Some(&"1".to_owned()));
+        assert_eq!(
+            pipeline.get_env().get("LEVEL"),
+            Some(&"pipeline".to_owned())
+        );
+    }
+}
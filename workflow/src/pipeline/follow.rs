@@ -0,0 +1,126 @@
+// Structs
+use super::run::print_line;
+use crate::types::{Command, Logs, Node, Pipeline, Step, StepOrParallel};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use uuid::Uuid;
+// Traits
+use pipelight_exec::{Statuable, Status};
+// Error Handling
+use miette::{IntoDiagnostic, Result};
+
+impl Pipeline {
+    /**
+    Follow a run, written by another process, until it finishes:
+    render the pipeline tree on every status change
+    and print the commands new output lines as they are written.
+    */
+    pub fn follow(&mut self) -> Result<()> {
+        let throttle = Duration::from_millis(200);
+        // Read offsets of the commands stdout and stderr files
+        let mut offsets: HashMap<Uuid, [u64; 2]> = HashMap::new();
+        let mut statuses = vec![];
+        loop {
+            // Checked before the reload, not to miss the last log update
+            let alive = self.is_running()?;
+            Logs::refresh();
+            *self = Logs::get_by_uuid(&self.uuid.to_string())?;
+            let finished = !alive
+                || !matches!(
+                    self.get_status(),
+                    Some(Status::Started) | Some(Status::Running)
+                );
+
+            for (step, command) in self.get_commands() {
+                let offsets = offsets.entry(command.process.io.uuid).or_default();
+                let flush = command.get_status() != Some(Status::Running);
+                let lines = command
+                    .process
+                    .io
+                    .read_new_lines(offsets, flush)
+                    .into_diagnostic()?;
+                for line in lines {
+                    print_line(step, &line);
+                }
+            }
+            let current = self.get_statuses();
+            if current != statuses {
+                println!("{}", Node::from(&*self));
+                statuses = current;
+            }
+
+            if finished {
+                return Ok(());
+            }
+            thread::sleep(throttle);
+        }
+    }
+    /**
+    Every command of the pipeline steps, with its step name.
+    */
+    fn get_commands(&self) -> Vec<(&str, &Command)> {
+        let mut commands = vec![];
+        for step_or_parallel in &self.steps {
+            let steps: Vec<&Step> = match step_or_parallel {
+                StepOrParallel::Step(step) => vec![step],
+                StepOrParallel::Parallel(parallel) => parallel.steps.iter().collect(),
+            };
+            for step in steps {
+                for command in &step.commands {
+                    commands.push((step.name.as_str(), command));
+                }
+            }
+        }
+        commands
+    }
+    /**
+    The pipeline, steps and commands statuses, to detect changes.
+    */
+    fn get_statuses(&self) -> Vec<Option<Status>> {
+        let mut statuses = vec![self.get_status()];
+        statuses.extend(self.steps.iter().map(|e| e.get_status()));
+        statuses.extend(self.get_commands().iter().map(|(_, e)| e.get_status()));
+        statuses
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Command, Logs, Pipeline, Step, StepOrParallel};
+    use miette::Result;
+    use pipelight_exec::Status;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn follow() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_follow".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "print".to_owned(),
+                commands: vec![Command::new("echo one; sleep 1; echo two")],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let uuid = p.uuid;
+        let handle = thread::spawn(move || p.run());
+
+        // Wait for the run to be logged
+        let start = Instant::now();
+        let mut followed = loop {
+            if let Ok(pipeline) = Logs::get_running_by_name("test_follow") {
+                break pipeline;
+            }
+            assert!(start.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(50));
+        };
+        assert_eq!(followed.uuid, uuid);
+
+        followed.follow()?;
+        assert_eq!(followed.status, Some(Status::Succeeded));
+        handle.join().unwrap()?;
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ mod test;
 use rustix::process::test_kill_process;
 // Structs
 use crate::types::{Logs, Pipeline, Trigger};
+use chrono::{DateTime, Local};
 use pipelight_utils::git::{Flag, Special};
 // Traits
 use pipelight_exec::Status;
@@ -39,15 +40,52 @@ impl Pipeline {
     If those conditions are met we assume the pipeline has an already running instance.
     */
     pub fn has_homologous_already_running(&self) -> Result<()> {
-        let mut pipelines = Logs::get_many_by_name(&self.name)?;
+        if !self.get_homologous_running()?.is_empty() {
+            return Ok(());
+        }
+        let message = "pipeline has no homologous already running";
+        Err(Error::msg(message))
+    }
+    /**
+    Return the running instances of the pipeline (same name),
+    the most recent first.
+    Finished runs of a process that is still alive are left out,
+    so are the runs queued after this one.
+    */
+    pub fn get_homologous_running(&self) -> Result<Vec<Pipeline>> {
+        Logs::refresh();
+        let mut pipelines = Logs::get_many_by_name(&self.name).unwrap_or_default();
         pipelines.reverse();
+        let mut running = vec![];
         for pipeline in pipelines {
-            if pipeline.is_running()? {
-                return Ok(());
+            if pipeline.uuid != self.uuid
+                && matches!(
+                    pipeline.status,
+                    Some(Status::Started) | Some(Status::Running)
+                )
+                && pipeline.is_running()?
+                && (pipeline.status == Some(Status::Running) || pipeline.is_ahead_of(self))
+            {
+                running.push(pipeline);
             }
         }
-        let message = "pipeline has no homologous already running";
-        Err(Error::msg(message))
+        Ok(running)
+    }
+    /**
+    Report if the run has been started, or queued, before the other one.
+    Runs that haven't been started yet come after every other.
+    */
+    fn is_ahead_of(&self, other: &Pipeline) -> bool {
+        let date = |e: &Pipeline| {
+            e.event
+                .as_ref()
+                .and_then(|e| e.date.parse::<DateTime<Local>>().ok())
+        };
+        match (date(self), date(other)) {
+            (Some(a), Some(b)) => (a, self.uuid) < (b, other.uuid),
+            (_, None) => true,
+            (None, Some(_)) => false,
+        }
     }
     /**
     Check if the pipeline instance(loaded from logs) is running.
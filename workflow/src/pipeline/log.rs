@@ -1,5 +1,5 @@
 // Structs
-use crate::types::{Pipeline, StepOrParallel};
+use crate::types::{Pipeline, Secret, StepOrParallel};
 // Traits
 use pipelight_exec::{Statuable, Status};
 // Globals
@@ -40,6 +40,16 @@ impl Pipeline {
         for process in processes {
             process.io.clean().into_diagnostic()?;
         }
+        // Step artifacts
+        let artifacts = self.get_artifacts_dir();
+        if artifacts.is_dir() {
+            fs::remove_dir_all(artifacts).into_diagnostic()?;
+        }
+        // Step output files
+        let outputs = self.get_outputs_dir();
+        if outputs.is_dir() {
+            fs::remove_dir_all(outputs).into_diagnostic()?;
+        }
         Ok(())
     }
     /**
@@ -50,7 +60,8 @@ impl Pipeline {
         let dir = OUTDIR.lock().unwrap();
         fs::create_dir_all(dir.clone()).into_diagnostic()?;
 
-        let json = serde_json::to_string(&self).unwrap() + "\n";
+        // Never write secret values, the command lines included
+        let json = Secret::redact(&serde_json::to_string(&self).unwrap()) + "\n";
 
         let stdout_path = format!("{}/{}.json", dir.clone(), self.uuid);
         let mut f = File::options()
@@ -1,7 +1,10 @@
+mod env;
 mod filters;
+mod follow;
 pub mod getters;
 mod is;
 mod log;
+mod resume;
 mod run;
 mod stop;
 
@@ -0,0 +1,135 @@
+// Structs
+use crate::types::{Pipeline, StepOrParallel, Trigger};
+use uuid::Uuid;
+// Traits
+use pipelight_exec::{Statuable, Status};
+// Error Handling
+use log::error;
+use miette::{Error, Result};
+
+impl Pipeline {
+    /**
+    Repeat a previous run:
+    restore its triggering environment and link the new run to it.
+    */
+    pub fn rerun(&mut self, previous: &Pipeline) -> Result<()> {
+        if previous.name != self.name {
+            let message = format!(
+                "The run {} belongs to pipeline {:?}, not {:?}",
+                previous.uuid, previous.name, self.name
+            );
+            return Err(Error::msg(message));
+        }
+        if let Some(event) = &previous.event {
+            Trigger::restore(&event.trigger)?;
+        }
+        self.origin = Some(previous.uuid);
+        Ok(())
+    }
+    /**
+    Resume a previous run.
+    The steps that come before the given step,
+    or before the first step that didn't succeed,
+    are reused from the previous run instead of being run again.
+
+    Only the pipeline is modified, so that a run can be checked
+    before being launched: the reused artifacts are copied when the run starts.
+    */
+    pub fn resume(&mut self, previous: &Pipeline, from: Option<&str>) -> Result<()> {
+        self.rerun(previous)?;
+        if let Some(from) = from {
+            if !self
+                .steps
+                .iter()
+                .any(|e| e.get_name().as_deref() == Some(from))
+            {
+                let message = format!("Couldn't find a step named {:?} in {:?}", from, self.name);
+                return Err(Error::msg(message));
+            }
+        }
+        for (step, old) in self.steps.iter_mut().zip(&previous.steps) {
+            // The pipeline definition has changed since the previous run
+            if step.get_name() != old.get_name() {
+                break;
+            }
+            match from {
+                Some(from) => {
+                    if step.get_name().as_deref() == Some(from) {
+                        break;
+                    }
+                    if !old.is_reusable() {
+                        let message = format!(
+                            "The step {:?} didn't succeed in run {}, it can't be reused",
+                            old.get_name().unwrap_or_default(),
+                            previous.uuid
+                        );
+                        return Err(Error::msg(message));
+                    }
+                }
+                None => {
+                    if !old.is_reusable() {
+                        break;
+                    }
+                }
+            }
+            *step = old.clone();
+            step.reuse();
+        }
+        Ok(())
+    }
+    /**
+    Report if some steps have been reused from the original run.
+    */
+    pub fn has_reused_steps(&self) -> bool {
+        self.steps.iter().any(|e| e.is_reused())
+    }
+}
+
+impl StepOrParallel {
+    fn is_reusable(&self) -> bool {
+        self.get_status() == Some(Status::Succeeded) || self.get_status() == Some(Status::Skipped)
+    }
+    /**
+    Mark the step, or every step of the parallel block, as reused.
+    */
+    fn reuse(&mut self) {
+        match self {
+            StepOrParallel::Step(step) => step.reused = Some(true),
+            StepOrParallel::Parallel(parallel) => {
+                for step in &mut parallel.steps {
+                    step.reused = Some(true);
+                }
+            }
+        }
+    }
+    /**
+    Copy the artifacts of the reused step, or of every step of the parallel block,
+    from the original run into the new run.
+    */
+    pub(crate) fn reuse_artifacts(&self, from: &Uuid, to: &Uuid) {
+        let steps = match self {
+            StepOrParallel::Step(step) => vec![step.as_ref()],
+            StepOrParallel::Parallel(parallel) => parallel.steps.iter().collect(),
+        };
+        for step in steps {
+            if let Err(err) = step.reuse_artifacts(from, to) {
+                error!(
+                    "Couldn't reuse the artifacts of step {}: {}",
+                    step.name, err
+                );
+            }
+        }
+    }
+    /**
+    Report if the step, or every step of the parallel block,
+    has been reused from the original run.
+    */
+    pub fn is_reused(&self) -> bool {
+        match self {
+            StepOrParallel::Step(step) => step.reused == Some(true),
+            StepOrParallel::Parallel(parallel) => {
+                !parallel.steps.is_empty() && parallel.steps.iter().all(|e| e.reused == Some(true))
+            }
+        }
+    }
+}
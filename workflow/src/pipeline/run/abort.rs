@@ -0,0 +1,119 @@
+// Structs
+use crate::types::{Cancel, Pipeline};
+use uuid::Uuid;
+// Signals
+use signal_hook::consts::signal::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+use signal_hook::low_level;
+// Error Handling
+use log::error;
+use miette::IntoDiagnostic;
+// Global var
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::slice;
+use std::sync::{Mutex, Once};
+use std::thread;
+
+/**
+Abortion flags of the pipeline runs, by run uuid.
+*/
+static ABORTED: Lazy<Mutex<HashMap<Uuid, Cancel>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/**
+Uuid of the pipeline run of this process, if any.
+*/
+static CURRENT: Lazy<Mutex<Option<Uuid>>> = Lazy::new(|| Mutex::new(None));
+static WATCH: Once = Once::new();
+
+/**
+Handle termination signals (Pipeline::stop, Ctrl-C, closed terminal)
+by gracefully aborting the running pipeline.
+Outside of a run, or on a second signal,
+the process is terminated right away.
+
+The signal thread only raises the run abortion flag,
+the threads running the steps cancel them.
+*/
+pub(super) fn watch_signals() {
+    WATCH.call_once(
+        || match Signals::new([SIGTERM, SIGINT, SIGHUP]).into_diagnostic() {
+            Ok(mut signals) => {
+                thread::spawn(move || {
+                    let mut aborted: Option<Uuid> = None;
+                    for signal in &mut signals {
+                        let current = *CURRENT.lock().unwrap();
+                        match current {
+                            Some(uuid) if aborted != Some(uuid) => {
+                                aborted = Some(uuid);
+                                abort_current();
+                            }
+                            _ => {
+                                low_level::emulate_default_handler(signal).ok();
+                            }
+                        }
+                    }
+                });
+            }
+            Err(err) => error!("Couldn't handle termination signals: {}", err),
+        },
+    );
+}
+
+/**
+Abort the pipeline run of this process.
+*/
+pub(super) fn abort_current() {
+    if let Some(uuid) = *CURRENT.lock().unwrap() {
+        get_flag(&uuid).raise();
+    }
+}
+
+/**
+The abortion flag of a pipeline run.
+*/
+fn get_flag(uuid: &Uuid) -> Cancel {
+    ABORTED.lock().unwrap().entry(*uuid).or_default().clone()
+}
+
+impl Pipeline {
+    /**
+    Gracefully abort a running pipeline:
+    no more steps are launched and the running ones are cancelled.
+    The executor then runs the on_abortion fallbacks and logs the final state.
+    */
+    pub fn abort(&self) {
+        get_flag(&self.uuid).raise();
+    }
+    /**
+    Report if the pipeline run has been asked to abort.
+    */
+    pub fn is_aborting(&self) -> bool {
+        ABORTED
+            .lock()
+            .unwrap()
+            .get(&self.uuid)
+            .is_some_and(|e| e.is_raised())
+    }
+    /**
+    Make the run the one aborted by termination signals,
+    and cancel its steps on abortion.
+    Fallbacks don't watch the abortion, so that they still run.
+    */
+    pub(super) fn watch_abort(&mut self) {
+        *CURRENT.lock().unwrap() = Some(self.uuid);
+        let flag = get_flag(&self.uuid);
+        for step in &mut self.steps {
+            step.watch(slice::from_ref(&flag));
+        }
+    }
+    /**
+    Clear the abortion request once the run is over.
+    */
+    pub(super) fn forget_abort(&self) {
+        ABORTED.lock().unwrap().remove(&self.uuid);
+        let mut current = CURRENT.lock().unwrap();
+        if *current == Some(self.uuid) {
+            *current = None;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// Structs
+use crate::types::{Concurrency, Event, Pipeline};
+use pipelight_exec::dates::Duration;
+// Traits
+use pipelight_exec::{Statuable, Status};
+// Error Handling
+use log::{info, warn};
+use miette::Result;
+// Time
+use std::thread;
+use std::time;
+
+/**
+Delay between two checks of the queued pipeline.
+*/
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(200);
+
+impl Pipeline {
+    /**
+    Report the policy to apply when an instance of the pipeline is already running.
+    */
+    pub fn get_concurrency(&self) -> Concurrency {
+        self.options
+            .as_ref()
+            .and_then(|e| e.concurrency.clone())
+            .unwrap_or_default()
+    }
+    /**
+    Apply the concurrency policy against the homologous running instances.
+    Returns false if the run must not happen,
+    in which case it is logged as skipped.
+    */
+    pub(super) fn wait_turn(&mut self) -> Result<bool> {
+        let concurrency = self.get_concurrency();
+        if concurrency == Concurrency::Allow {
+            return Ok(true);
+        }
+        let running = self.get_homologous_running()?;
+        if running.is_empty() {
+            return Ok(true);
+        }
+        match concurrency {
+            Concurrency::Skip => {
+                warn!(
+                    "Skipping pipeline {}: an instance is already running",
+                    self.name
+                );
+                self.skip()?;
+                return Ok(false);
+            }
+            Concurrency::Queue => {
+                info!(
+                    "Pipeline {} queued: waiting for the running instances to finish",
+                    self.name
+                );
+                // Logged so that the next queued runs wait for this one
+                self.event = Some(Event::default());
+                self.set_status(Some(Status::Started));
+                self.log()?;
+                while !self.get_homologous_running()?.is_empty() {
+                    thread::sleep(POLL_INTERVAL);
+                }
+            }
+            Concurrency::CancelPrevious => {
+                for pipeline in running {
+                    info!("Cancelling pipeline run {}", pipeline.uuid);
+                    pipeline.cancel()?;
+                }
+            }
+            Concurrency::Allow => {}
+        }
+        Ok(true)
+    }
+    /**
+    Log the run as skipped instead of running it.
+    */
+    fn skip(&mut self) -> Result<()> {
+        let mut d = Duration::default();
+        d.start()?;
+        d.stop()?;
+        self.duration = Some(d);
+        self.event = Some(Event::default());
+        self.set_status(Some(Status::Skipped));
+        self.log()
+    }
+}
@@ -0,0 +1,165 @@
+// Structs
+use super::shared::{Seg, Shared};
+use crate::types::{Mode, Pipeline, StepOrParallel};
+// Traits
+use pipelight_exec::{Statuable, Status};
+// Threads
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+// Error Handling
+use miette::{Error, IntoDiagnostic, Result};
+
+/**
+The execution state of a step in the pipeline dependency graph.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Vertex {
+    Pending,
+    Running,
+    Done,
+}
+
+impl StepOrParallel {
+    /**
+    A done step lets its dependents start
+    if it has succeeded or if its failure mode lets the pipeline go on.
+    */
+    fn is_satisfying(&self) -> bool {
+        self.get_status() == Some(Status::Succeeded)
+            || self.get_status() == Some(Status::Skipped)
+            || self.get_mode() == Some(Mode::JumpNextOnFailure)
+            || self.get_mode() == Some(Mode::ContinueOnFailure)
+    }
+}
+
+impl Pipeline {
+    /**
+    Report if some of the pipeline steps declare dependencies.
+    */
+    pub fn has_needs(&self) -> bool {
+        self.steps.iter().any(|e| !e.get_needs().is_empty())
+    }
+    /**
+    Convert every step dependencies (step names) into step indexes.
+    */
+    fn resolve_needs(&self) -> Result<Vec<Vec<usize>>> {
+        let mut graph: Vec<Vec<usize>> = vec![];
+        for step in &self.steps {
+            let mut needs: Vec<usize> = vec![];
+            for need in step.get_needs() {
+                let indexes: Vec<usize> = self
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| e.get_name() == Some(need.clone()))
+                    .map(|(i, _)| i)
+                    .collect();
+                if indexes.is_empty() {
+                    let message = format!("Couldn't find the needed step: {}", need);
+                    return Err(Error::msg(message));
+                }
+                needs.extend(indexes);
+            }
+            graph.push(needs);
+        }
+        Ok(graph)
+    }
+    /**
+    Run the pipeline steps as a dependency graph.
+
+    Steps without dependencies start immediately,
+    the others start as soon as the steps they need are done.
+    Ready steps run concurrently.
+
+    A step that fails in StopOnFailure mode only holds back its dependents,
+    steps of unrelated branches go on.
+    The pipeline abortion, or an error, prevents new steps from being launched,
+    already running steps are awaited.
+    The pipeline is logged on every step launch and completion.
+
+    Every launched step is moved to its thread, and sent back once done.
+    */
+    pub(super) fn run_graph(&mut self, shared: &Shared, deadline: Option<Instant>) -> Result<()> {
+        let needs = self.resolve_needs()?;
+        // The steps that are not running
+        let mut steps: Vec<Option<&mut StepOrParallel>> = self.steps.iter_mut().map(Some).collect();
+        let mut vertices = vec![Vertex::Pending; needs.len()];
+        // The first error, returned once running steps are done
+        let mut error: Option<Error> = None;
+
+        let (tx, rx) = mpsc::channel::<(usize, &mut StepOrParallel, Result<()>)>();
+        thread::scope(|s| {
+            loop {
+                // Launch ready steps
+                if error.is_none() {
+                    let mut launched = false;
+                    for i in 0..vertices.len() {
+                        let is_ready = vertices[i] == Vertex::Pending
+                            && steps[i].as_ref().is_some_and(|e| !e.is_interrupted())
+                            && needs[i].iter().all(|j| {
+                                vertices[*j] == Vertex::Done
+                                    && steps[*j].as_ref().is_some_and(|e| e.is_satisfying())
+                            });
+                        if !is_ready {
+                            continue;
+                        }
+                        if let Some(step) = steps[i].take() {
+                            vertices[i] = Vertex::Running;
+                            let shared = shared.join(Seg::Step(i));
+                            let tx = tx.clone();
+                            s.spawn(move || {
+                                let res = step.run(&shared, deadline);
+                                tx.send((i, step, res)).ok();
+                            });
+                            launched = true;
+                        }
+                    }
+                    if launched {
+                        if let Err(err) = shared.log() {
+                            error = Some(err);
+                        }
+                    }
+                }
+                if !vertices.contains(&Vertex::Running) {
+                    break;
+                }
+                // Wait for a step to be done
+                match rx.recv().into_diagnostic() {
+                    Ok((i, step, res)) => {
+                        vertices[i] = Vertex::Done;
+                        steps[i] = Some(step);
+                        let res = res.and_then(|_| shared.log());
+                        if let Err(err) = res {
+                            error.get_or_insert(err);
+                        }
+                    }
+                    Err(err) => {
+                        error.get_or_insert(err);
+                        break;
+                    }
+                }
+            }
+        });
+        match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+    /**
+    Compute the pipeline status once its dependency graph has been run.
+    Steps that have never been launched fail the pipeline.
+    */
+    pub(super) fn get_graph_status(&self) -> Status {
+        let mut status = Status::Succeeded;
+        for step in &self.steps {
+            match step.get_status() {
+                Some(Status::Succeeded) | Some(Status::Skipped) => {}
+                Some(_) if step.get_mode() == Some(Mode::JumpNextOnFailure) => {}
+                Some(Status::Aborted) => return Status::Aborted,
+                _ => status = Status::Failed,
+            }
+        }
+        status
+    }
+}
@@ -1,61 +1,122 @@
 // Types
-use crate::types::{Command, Event, Mode, Parallel, Pipeline, Step, StepOrParallel};
+use crate::types::{
+    Attempt, Cancel, Command, Event, Fallback, Mode, Parallel, Pipeline, Secret, Step,
+    StepOrParallel,
+};
+use pipelight_exec::dates::convert::human_duration_to_std_duration;
 use pipelight_exec::dates::Duration;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+use uuid::Uuid;
 // Error Handling
+use log::error;
 use miette::Result;
 // Traits
-use pipelight_exec::{Statuable, Status};
+use pipelight_exec::{Line, Statuable, Status, Stream};
+// Colors and Formatting
+use colored::Colorize;
 // Global var
-use once_cell::sync::Lazy;
+use crate::globals::LIVE_OUTPUT;
 // Parallelism
-use rayon::prelude::*;
+use crate::scheduler::{Scheduler, JOBS};
+
+mod abort;
+mod concurrency;
+mod graph;
+mod shared;
+use shared::{Hook, Seg, Shared};
 // Tests
 mod test;
 
-// Global var
-static mut PIPELINE: Lazy<Pipeline> = Lazy::new(Pipeline::default);
+/**
+Compute the instant at which an execution must be killed,
+from its own timeout and its parent deadline.
+The earliest wins.
+*/
+fn get_deadline(timeout: &Option<String>, parent: Option<Instant>) -> Result<Option<Instant>> {
+    let own = match timeout {
+        Some(timeout) => Some(Instant::now() + human_duration_to_std_duration(timeout)?),
+        None => None,
+    };
+    Ok(match (own, parent) {
+        (Some(own), Some(parent)) => Some(own.min(parent)),
+        (own, parent) => own.or(parent),
+    })
+}
+/**
+Report whether a deadline has been reached.
+*/
+fn is_expired(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|e| Instant::now() >= e)
+}
 
 impl Pipeline {
     /// Execute the pipeline
     pub fn run(&mut self) -> Result<()> {
-        // Globals
-        let ptr: *mut Pipeline;
-        unsafe {
-            ptr = &mut *PIPELINE;
-            *ptr = self.to_owned();
-        }
         // Guards
-        unsafe {
-            if (*ptr).has_homologous_already_running().is_ok() {
-                return Ok(());
-            }
-            if (*ptr).triggers.is_some() {}
+        if !self.wait_turn()? {
+            return Ok(());
         }
+        // Abort gracefully on termination signals
+        abort::watch_signals();
+        self.watch_abort();
 
         // Duration
         let mut d = Duration::default();
         d.start()?;
-        unsafe {
-            (*ptr).duration = Some(d.clone());
-        }
+        self.duration = Some(d.clone());
 
-        // Event
-        let event = Event::default();
+        // Timeout
+        let deadline = get_deadline(&self.timeout, None)?;
+
+        // Event, kept from the queue not to lose the run place in it
+        let event = match self.get_status() {
+            Some(Status::Started) => self.event.clone().unwrap_or_default(),
+            _ => Event::default(),
+        };
 
         // Set event = Pid , Status and Duration
-        unsafe {
-            (*ptr).event = Some(event);
-            (*ptr).set_status(Some(Status::Started));
-            (*ptr).log()?;
-        }
+        self.event = Some(event);
+        self.set_status(Some(Status::Started));
+        // Read by the logs and the steps lookups while the steps run
+        let shared = Mutex::new(self.clone());
+        let root = Shared::new(&shared);
+        root.publish(self)?;
+
+        self.set_status(Some(Status::Running));
+        root.publish(self)?;
+
+        Pipeline::execute(self, &root, deadline, &mut d)?;
 
-        unsafe {
-            (*ptr).set_status(Some(Status::Running));
-            (*ptr).log()?;
+        self.forget_abort();
+        Ok(())
+    }
+    /**
+    Run the pipeline steps and fallbacks, then set the pipeline status.
+    The pipeline is the run pipeline, or a pipeline invoked by one of its steps,
+    and publishes itself to the shared run pipeline.
+    */
+    fn execute(
+        pipeline: &mut Pipeline,
+        shared: &Shared,
+        deadline: Option<Instant>,
+        d: &mut Duration,
+    ) -> Result<()> {
+        if let Some(fallback) = pipeline.fallback.as_mut() {
+            fallback.run_hook(Hook::Started, shared)?;
+        }
 
-            for step in &mut (*ptr).steps {
-                step.run(ptr)?;
-                if (step.get_status() != Some(Status::Succeeded))
+        if pipeline.has_needs() {
+            pipeline.run_graph(shared, deadline)?;
+        } else {
+            for (i, step) in pipeline.steps.iter_mut().enumerate() {
+                if step.is_interrupted() {
+                    break;
+                }
+                step.run(&shared.join(Seg::Step(i)), deadline)?;
+                if (step.get_status() != Some(Status::Succeeded)
+                    && step.get_status() != Some(Status::Skipped))
                     && (step.get_mode().is_none() || step.get_mode() == Some(Mode::StopOnFailure))
                 {
                     break;
@@ -65,76 +126,88 @@ impl Pipeline {
 
         // Duration
         d.stop()?;
-        unsafe {
-            (*ptr).duration = Some(d.clone());
-        }
+        pipeline.duration = Some(d.clone());
 
         // Set pipeline status to last Step status
-        unsafe {
-            let last_step = (*ptr).steps.last().unwrap();
-            if last_step.get_status().is_some() {
-                if last_step.get_mode() == Some(Mode::JumpNextOnFailure) {
-                    if last_step.get_status() == Some(Status::Failed) {
-                        (*ptr).set_status(Some(Status::Succeeded))
-                    } else {
-                        (*ptr).set_status(last_step.get_status())
-                    }
+        let status = if pipeline.has_needs() {
+            pipeline.get_graph_status()
+        } else if let Some(last_step) = pipeline.steps.last() {
+            if last_step.get_status() == Some(Status::Skipped) {
+                Status::Succeeded
+            } else if let Some(status) = last_step.get_status() {
+                if last_step.get_mode() == Some(Mode::JumpNextOnFailure)
+                    && (status == Status::Failed || status == Status::TimedOut)
+                {
+                    Status::Succeeded
                 } else {
-                    (*ptr).set_status(last_step.get_status())
+                    status
                 }
             } else {
-                (*ptr).set_status(Some(Status::Failed))
+                Status::Failed
+            }
+        } else {
+            // Nothing to run (ex: an invoked pipeline without steps)
+            Status::Succeeded
+        };
+        pipeline.set_status(Some(status));
+        if pipeline.steps.iter().any(|e| e.is_interrupted()) {
+            pipeline.set_status(Some(Status::Aborted))
+        }
+        // A step timeout fails the pipeline,
+        // only the pipeline own timeout times it out.
+        if pipeline.status != Some(Status::Succeeded) {
+            if is_expired(deadline) {
+                pipeline.set_status(Some(Status::TimedOut))
+            } else if pipeline.status == Some(Status::TimedOut) {
+                pipeline.set_status(Some(Status::Failed))
             }
-            (*ptr).log()?;
         }
+        shared.publish(pipeline)?;
 
         // Execute fallbacks
-        unsafe {
-            if (*ptr).fallback.is_some() {
-                let fallback = &mut (*ptr).fallback.as_mut().unwrap();
-                if (*ptr).status == Some(Status::Failed) && fallback.on_failure.is_some() {
-                    // let steps = (*ptr).on_failure.as_mut().unwrap();
-                    for step in fallback.on_failure.as_mut().unwrap() {
-                        step.run(ptr)?;
-                    }
-                }
-                if (*ptr).status == Some(Status::Succeeded) && fallback.on_failure.is_some() {
-                    // let steps = (*ptr).on_failure.as_mut().unwrap();
-                    for step in fallback.on_success.as_mut().unwrap() {
-                        step.run(ptr)?;
-                    }
-                }
-                if (*ptr).status == Some(Status::Aborted) && fallback.on_success.is_some() {
-                    // let steps = (*ptr).on_failure.as_mut().unwrap();
-                    for step in fallback.on_abortion.as_mut().unwrap() {
-                        step.run(ptr)?;
-                    }
-                }
-                // Duration
-                d.stop()?;
-                (*ptr).duration = Some(d);
-                (*ptr).log()?;
+        let status = pipeline.status.clone();
+        if let Some(fallback) = pipeline.fallback.as_mut() {
+            if status == Some(Status::Failed) {
+                fallback.run_hook(Hook::Failure, shared)?;
             }
-        }
-        unsafe {
-            let global_pipe = &mut (*ptr);
-            *self = global_pipe.to_owned();
+            if status == Some(Status::Succeeded) {
+                fallback.run_hook(Hook::Success, shared)?;
+            }
+            // A timed out pipeline has been aborted by pipelight.
+            if status == Some(Status::Aborted) || status == Some(Status::TimedOut) {
+                fallback.run_hook(Hook::Abortion, shared)?;
+            }
+            fallback.run_on_finished(shared)?;
+            // Duration
+            d.stop()?;
+            pipeline.duration = Some(d.clone());
+            shared.publish(pipeline)?;
         }
         Ok(())
     }
 }
 
 impl StepOrParallel {
-    fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
+    fn run(&mut self, shared: &Shared, deadline: Option<Instant>) -> Result<()> {
+        // Result taken from the original run
+        if self.is_reused() {
+            let (origin, uuid) = shared.read(|e| (e.origin, e.uuid));
+            if let Some(origin) = origin {
+                self.reuse_artifacts(&origin, &uuid);
+            }
+            return Ok(());
+        }
         match self {
-            StepOrParallel::Step(res) => res.run(ptr),
-            StepOrParallel::Parallel(res) => res.run(ptr),
+            StepOrParallel::Step(res) => res.run(shared, deadline),
+            StepOrParallel::Parallel(res) => res.run(shared, deadline),
         }
     }
 }
 
 impl Parallel {
-    fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
+    fn run(&mut self, shared: &Shared, deadline: Option<Instant>) -> Result<()> {
+        // Timeout
+        let deadline = get_deadline(&self.timeout, deadline)?;
         // Duration
         let mut d = Duration::default();
         d.start()?;
@@ -142,21 +215,49 @@ impl Parallel {
 
         self.set_status(Some(Status::Running));
 
-        // Pass wrapped pointer to threads
-        let ptr_wrapper = PtrWrapper(ptr);
-        self.steps
-            .par_iter_mut()
-            .for_each(|e| e.unsafe_run(ptr_wrapper).unwrap());
+        let options = self.options.clone().unwrap_or_default();
+        let fail_fast = options.fail_fast.unwrap_or_default();
+        let stop_on_failure = options.mode == Some(Mode::StopOnFailure);
+        // Raised from any thread to cancel the whole block
+        let siblings: Vec<Cancel> = self
+            .steps
+            .iter()
+            .map(|e| e.cancelled.flag.clone())
+            .collect();
+        Scheduler::new(options.max_parallel).for_each(&mut self.steps, |i, step| {
+            if step.is_cancelled() {
+                return Ok(false);
+            }
+            step.run(&shared.join(Seg::Step(i)), deadline)?;
+            let failed = step.get_status() != Some(Status::Succeeded)
+                && step.get_status() != Some(Status::Skipped)
+                && step.get_status() != Some(Status::Aborted);
+            if failed && stop_on_failure {
+                for flag in &siblings {
+                    flag.raise();
+                }
+            }
+            Ok(!((fail_fast || stop_on_failure) && failed))
+        })?;
+
+        // Steps cancelled by a failure
+        for step in &mut self.steps {
+            if step.get_status().is_none() {
+                step.set_status(Some(Status::Aborted));
+            }
+        }
 
         // Set parallel global status
         let steps_res: Vec<Status> = self
             .steps
             .iter()
-            .map(|e| e.clone().status.unwrap())
+            .map(|e| e.clone().status.unwrap_or(Status::Aborted))
             .collect();
 
         if steps_res.contains(&Status::Failed) {
             self.set_status(Some(Status::Failed));
+        } else if steps_res.contains(&Status::TimedOut) {
+            self.set_status(Some(Status::TimedOut));
         } else if steps_res.contains(&Status::Aborted) {
             self.set_status(Some(Status::Aborted));
         } else {
@@ -167,98 +268,398 @@ impl Parallel {
         d.stop()?;
         self.duration = Some(d);
 
-        unsafe {
-            (*ptr).log()?;
-        }
+        shared.publish(self)?;
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-struct PtrWrapper(*mut Pipeline);
-unsafe impl Sync for PtrWrapper {}
-unsafe impl Send for PtrWrapper {}
 impl Step {
-    fn unsafe_run(&mut self, ptr: PtrWrapper) -> Result<()> {
-        let ptr = ptr.0;
-        self.run(ptr)
-    }
-    fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
+    fn run(&mut self, shared: &Shared, deadline: Option<Instant>) -> Result<()> {
+        // Condition
+        if !shared.read(|e| self.is_condition_met(e))? {
+            self.set_status(Some(Status::Skipped));
+            shared.publish(self)?;
+            return Ok(());
+        }
+        // Cache
+        if self.inputs.is_some() {
+            // The inputs are hashed out of the lock
+            let pipeline = shared.read(|e| e.clone());
+            match self.restore_cache(&pipeline) {
+                Ok(true) => {
+                    self.set_status(Some(Status::Succeeded));
+                    shared.publish(self)?;
+                    return Ok(());
+                }
+                Ok(false) => {}
+                Err(err) => error!("Couldn't read the cache of step {}: {}", self.name, err),
+            }
+        }
         // Options
         let mode = self.get_mode();
+        // Timeout
+        let deadline = get_deadline(&self.timeout, deadline)?;
         // Duration
         let mut d = Duration::default();
         d.start()?;
         self.duration = Some(d.clone());
 
         self.set_status(Some(Status::Running));
+        shared.publish(self)?;
 
-        // Run commands
-        for command in &mut self.commands {
-            command.run(ptr)?;
-
-            if (command.get_status().is_none() || command.get_status() != Some(Status::Succeeded))
-                && (mode.is_none() || mode != Some(Mode::ContinueOnFailure))
-            {
-                break;
-            }
+        // Run before taking a job slot, fallback steps take their own
+        if let Some(fallback) = self.fallback.as_mut() {
+            fallback.run_hook(Hook::Started, shared)?;
         }
 
-        // Set global status after run
-        let final_status = &self.commands.last().unwrap().get_status();
-        if final_status.is_some() {
-            self.status = final_status.clone();
+        if self.pipeline.is_some() {
+            // Invoked pipeline: its own steps take the job slots
+            self.run_child(shared, deadline)?;
         } else {
-            self.set_status(Some(Status::Failed))
+            // Wait for a free job slot, released before the fallbacks run
+            let job = JOBS.acquire();
+            self.run_attempts(shared, deadline, &mode)?;
+            drop(job);
+            let uuid = shared.read(|e| e.uuid);
+            if let Err(err) = self.collect_outputs(&uuid) {
+                error!("Couldn't read the outputs of step {}: {}", self.name, err);
+            }
         }
+        self.forget_cancel();
 
         // Duration
         d.stop()?;
         self.duration = Some(d);
 
-        unsafe {
-            (*ptr).log()?;
+        // Artifacts
+        if self.artifacts.is_some() {
+            let uuid = shared.read(|e| e.uuid);
+            if let Err(err) = self.collect_artifacts(&uuid) {
+                error!(
+                    "Couldn't collect the artifacts of step {}: {}",
+                    self.name, err
+                );
+            }
+        }
+        if self.status == Some(Status::Succeeded) && self.inputs.is_some() {
+            let uuid = shared.read(|e| e.uuid);
+            if let Err(err) = self.store_cache(&uuid) {
+                error!("Couldn't store the cache of step {}: {}", self.name, err);
+            }
         }
+
+        shared.publish(self)?;
         // Execute post-run steps
-        if self.fallback.is_some() {
-            let fallback = &mut self.fallback.as_mut().unwrap();
-            if self.status == Some(Status::Failed) && fallback.on_failure.is_some() {
-                for step in fallback.on_failure.as_mut().unwrap() {
-                    step.run(ptr)?;
+        if let Some(fallback) = self.fallback.as_mut() {
+            // A timed out step is a failed step.
+            if self.status == Some(Status::Failed) || self.status == Some(Status::TimedOut) {
+                fallback.run_hook(Hook::Failure, shared)?;
+            }
+            if self.status == Some(Status::Succeeded) {
+                fallback.run_hook(Hook::Success, shared)?;
+            }
+            if self.status == Some(Status::Aborted) {
+                fallback.run_hook(Hook::Abortion, shared)?;
+            }
+            fallback.run_on_finished(shared)?;
+            shared.publish(self)?;
+        }
+        Ok(())
+    }
+    /**
+    Run the step commands until success or until the retry policy is exhausted,
+    and set the step status.
+    */
+    fn run_attempts(
+        &mut self,
+        shared: &Shared,
+        deadline: Option<Instant>,
+        mode: &Option<Mode>,
+    ) -> Result<()> {
+        // Run commands, again on failure if the step has a retry policy
+        let retry = self.get_retry();
+        // Command lines before interpolation, masked once run
+        let stdins: Vec<String> = self
+            .commands
+            .iter()
+            .map(|e| e.process.io.stdin.clone().unwrap_or_default())
+            .collect();
+        loop {
+            let mut attempt_d = Duration::default();
+            attempt_d.start()?;
+            let mut status = self.run_commands(shared, deadline, mode)?;
+            attempt_d.stop()?;
+            // Cancelled while running
+            if self.is_cancelled() && status != Status::Succeeded {
+                status = Status::Aborted;
+            }
+
+            let attempt = self.get_attempt();
+            let can_retry = match &retry {
+                Some(retry) => {
+                    attempt < retry.attempts
+                        && status != Status::Succeeded
+                        && status != Status::Aborted
+                        && !is_expired(deadline)
                 }
+                None => false,
+            };
+            if !can_retry {
+                self.set_status(Some(status));
+                break;
+            }
+
+            // Keep the failed attempt and reset commands for the next one
+            self.attempts.get_or_insert(vec![]).push(Attempt {
+                status: Some(status),
+                duration: Some(attempt_d),
+                commands: self.commands.clone(),
+            });
+            self.commands = stdins.iter().map(|e| Command::new(e)).collect();
+            shared.publish(self)?;
+
+            // Wait before next attempt, never beyond the deadline
+            let mut delay = retry.as_ref().unwrap().get_delay(attempt)?;
+            if let Some(deadline) = deadline {
+                delay = delay.min(deadline.saturating_duration_since(Instant::now()));
+            }
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+    /**
+    Run the invoked pipeline, nested under the step,
+    and take its status.
+    */
+    fn run_child(&mut self, shared: &Shared, deadline: Option<Instant>) -> Result<()> {
+        let flags = self.get_cancel_flags();
+        let Some(child) = self.child.as_mut() else {
+            error!(
+                "Couldn't find the pipeline {:?} invoked by step {}",
+                self.pipeline.clone().unwrap_or_default(),
+                self.name
+            );
+            self.set_status(Some(Status::Failed));
+            return Ok(());
+        };
+        // A new run of the invoked pipeline, cancelled along with the step
+        child.uuid = Uuid::new_v4();
+        for step in &mut child.steps {
+            step.watch(&flags);
+        }
+        let deadline = get_deadline(&child.timeout, deadline)?;
+        let mut d = Duration::default();
+        d.start()?;
+        child.duration = Some(d.clone());
+        child.set_status(Some(Status::Running));
+        let shared = shared.join(Seg::Child);
+        shared.publish(child.as_ref())?;
+        Pipeline::execute(child, &shared, deadline, &mut d)?;
+
+        let mut status = child.get_status().unwrap_or(Status::Failed);
+        // Cancelled while running
+        if self.is_cancelled() && status != Status::Succeeded {
+            status = Status::Aborted;
+        }
+        self.set_status(Some(status));
+        Ok(())
+    }
+    /**
+    Run the step commands once and return the resulting step status.
+    */
+    fn run_commands(
+        &mut self,
+        shared: &Shared,
+        deadline: Option<Instant>,
+        mode: &Option<Mode>,
+    ) -> Result<Status> {
+        // Referenced secrets, overridden by the step own environment
+        let mut env = self.get_secrets();
+        env.extend(self.get_env());
+        // A fresh output file for every attempt
+        let uuid = shared.read(|e| e.uuid);
+        let output = self.prepare_outputs(&uuid)?;
+        env.insert("PIPELIGHT_OUTPUT".to_owned(), output.display().to_string());
+        let cwd = self.options.as_ref().and_then(|e| e.cwd.clone());
+        let limits = self.options.as_ref().and_then(|e| e.limits.clone());
+        // The output file stays writable in the read-only project
+        let sandbox = self
+            .options
+            .as_ref()
+            .and_then(|e| e.sandbox.clone())
+            .map(|mut e| {
+                e.writable.push(output.display().to_string());
+                e
+            });
+        let flags = self.get_cancel_flags();
+        for i in 0..self.commands.len() {
+            // Cancelled before the command starts
+            if self.is_cancelled() {
+                return Ok(Status::Aborted);
             }
-            if self.status == Some(Status::Succeeded) && fallback.on_success.is_some() {
-                for step in fallback.on_success.as_mut().unwrap() {
-                    step.run(ptr)?;
+            // Resolve the values of the previous steps (ex: outputs)
+            let resolved = match self.commands[i].process.io.stdin.clone() {
+                Some(stdin) => shared.read(|e| self.resolve_command(e, &stdin)).map(Some),
+                None => Ok(None),
+            };
+            let command = &mut self.commands[i];
+            match resolved {
+                Ok(Some((stdin, vars))) => {
+                    command.process.io.stdin = Some(stdin);
+                    command.process.envs(&vars);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    // Never handed to the shell
+                    error!("Couldn't run a command of step {}: {}", self.name, err);
+                    command.process.io.stderr = Some(format!("{}\n", err));
+                    command.set_status(Some(Status::Failed));
+                    shared.join(Seg::Command(i)).publish(command)?;
+                    if mode != &Some(Mode::ContinueOnFailure) {
+                        break;
+                    }
+                    continue;
                 }
             }
-            if self.status == Some(Status::Aborted) && fallback.on_abortion.is_some() {
-                for step in fallback.on_success.as_mut().unwrap() {
-                    step.run(ptr)?;
+            command.process.envs(&env);
+            // Killed by this thread once the step is cancelled
+            for flag in &flags {
+                command.process.cancel_on(flag);
+            }
+            if let Some(cwd) = &cwd {
+                command.process.current_dir(cwd);
+            }
+            if let Some(sandbox) = &sandbox {
+                command.process.sandbox(sandbox);
+            }
+            if let Some(limits) = &limits {
+                command.process.limits(limits);
+            }
+            command.run(&shared.join(Seg::Command(i)), deadline, &self.name)?;
+
+            // Cancelled while running: the command has been killed
+            let status = command.get_status();
+            if self.is_cancelled() {
+                if status != Some(Status::Succeeded) {
+                    self.commands[i].set_status(Some(Status::Aborted));
                 }
+                return Ok(Status::Aborted);
+            }
+            if (status.is_none() || status != Some(Status::Succeeded))
+                && (mode.is_none() || mode != &Some(Mode::ContinueOnFailure))
+            {
+                break;
             }
-            unsafe {
-                (*ptr).log()?;
+        }
+
+        // Set global status after run
+        let final_status = &self.commands.last().unwrap().get_status();
+        if self
+            .commands
+            .iter()
+            .any(|e| e.get_status() == Some(Status::TimedOut))
+        {
+            Ok(Status::TimedOut)
+        } else if let Some(final_status) = final_status {
+            Ok(final_status.to_owned())
+        } else {
+            Ok(Status::Failed)
+        }
+    }
+}
+
+impl Fallback {
+    /**
+    Run the steps of a hook, if any.
+    The given handle is the one of the pipeline or step the fallback belongs to.
+    */
+    fn run_hook(&mut self, hook: Hook, shared: &Shared) -> Result<()> {
+        if let Some(steps) = self.get_hook(hook) {
+            for (i, step) in steps.iter_mut().enumerate() {
+                step.run(&shared.join(Seg::Fallback(hook, i)), None)?;
             }
         }
         Ok(())
     }
+    /**
+    Run every on_finished step, whatever the outcome of the others,
+    and record their status apart from the pipeline or step status:
+    the first unsuccessful step status, or Succeeded.
+    */
+    fn run_on_finished(&mut self, shared: &Shared) -> Result<()> {
+        let Some(steps) = self.on_finished.as_mut() else {
+            return Ok(());
+        };
+        let mut status = Status::Succeeded;
+        for (i, step) in steps.iter_mut().enumerate() {
+            step.run(&shared.join(Seg::Fallback(Hook::Finished, i)), None)?;
+            match step.get_status() {
+                Some(Status::Succeeded) | Some(Status::Skipped) => {}
+                res => {
+                    if status == Status::Succeeded {
+                        status = res.unwrap_or(Status::Failed);
+                    }
+                }
+            }
+        }
+        self.on_finished_status = Some(status);
+        Ok(())
+    }
+}
+
+/**
+Print a command output line as it arrives, under its step name.
+*/
+pub(super) fn print_line(step: &str, line: &Line) {
+    let prefix = format!("{} │", step);
+    match line.stream {
+        Stream::Stdout => println!("{} {}", prefix.white(), line.text),
+        Stream::Stderr => eprintln!("{} {}", prefix.white(), line.text.red()),
+    }
 }
 
 impl Command {
-    fn run(&mut self, ptr: *mut Pipeline) -> Result<()> {
+    fn run(&mut self, shared: &Shared, deadline: Option<Instant>, step: &str) -> Result<()> {
+        // Deadline already reached: do not spawn the process.
+        if is_expired(deadline) {
+            self.set_status(Some(Status::TimedOut));
+            shared.publish(self)?;
+            return Ok(());
+        }
+
         // Duration
         let mut d = Duration::default();
         d.start()?;
         self.duration = Some(d.clone());
 
         self.set_status(Some(Status::Running));
-        unsafe {
-            (*ptr).log()?;
+        shared.publish(self)?;
+
+        // Export run metadata
+        let env = shared.read(|e| e.get_metadata_env(step));
+        self.process.envs(&env);
+
+        // Bound process execution to the remaining time
+        if let Some(deadline) = deadline {
+            self.process
+                .timeout(deadline.saturating_duration_since(Instant::now()));
         }
 
-        // Run process
-        let res = self.process.term().fs().run();
+        // Run process, in its own process group to be cancellable
+        self.process
+            .term()
+            .fs()
+            .group()
+            .secrets(&Secret::get_values());
+        // Always streamed, so that the output files are masked as they are written
+        // and can be followed from another process.
+        // Done once the command exits, even if a backgrounded child holds its pipes.
+        let live = *LIVE_OUTPUT.lock().unwrap();
+        let res = self.process.stream(|line| {
+            if live {
+                print_line(step, line)
+            }
+        });
         let _ = match res {
             Ok(_) => Ok(()),
             Err(e) => {
@@ -271,9 +672,7 @@ impl Command {
         d.stop()?;
         self.duration = Some(d);
 
-        unsafe {
-            (*ptr).log()?;
-        }
+        shared.publish(self)?;
         Ok(())
     }
 }
@@ -0,0 +1,255 @@
+// Structs
+use crate::types::{Command, Fallback, Parallel, Pipeline, Step, StepOrParallel};
+// Error Handling
+use miette::Result;
+// Threads
+use std::sync::Mutex;
+
+/**
+The fallback hooks of a pipeline or of a step.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Hook {
+    Started,
+    Failure,
+    Success,
+    Abortion,
+    Finished,
+}
+
+impl Fallback {
+    pub(super) fn get_hook(&mut self, hook: Hook) -> Option<&mut Vec<StepOrParallel>> {
+        match hook {
+            Hook::Started => self.on_started.as_mut(),
+            Hook::Failure => self.on_failure.as_mut(),
+            Hook::Success => self.on_success.as_mut(),
+            Hook::Abortion => self.on_abortion.as_mut(),
+            Hook::Finished => self.on_finished.as_mut(),
+        }
+    }
+}
+
+/**
+A segment of the path from the run pipeline to one of its parts.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Seg {
+    // A step of a pipeline or of a parallel block
+    Step(usize),
+    // A step of a pipeline or step fallback
+    Fallback(Hook, usize),
+    // A command of a step
+    Command(usize),
+    // The pipeline invoked by a step
+    Child,
+}
+
+/**
+The pipeline being run, shared between the threads running its steps.
+
+Every thread only mutates its own part of the run (a step, a command...)
+and publishes a snapshot of it to the shared pipeline under the lock.
+Logs and lookups of other steps read the shared pipeline under the same lock,
+so that they never see a part in the middle of an update.
+*/
+#[derive(Debug, Clone)]
+pub(super) struct Shared<'a> {
+    pipeline: &'a Mutex<Pipeline>,
+    // Where the owner of this handle is in the shared pipeline
+    path: Vec<Seg>,
+}
+impl<'a> Shared<'a> {
+    pub fn new(pipeline: &'a Mutex<Pipeline>) -> Self {
+        Shared {
+            pipeline,
+            path: vec![],
+        }
+    }
+    /**
+    The handle of a part of the owner of this handle.
+    */
+    pub fn join(&self, seg: Seg) -> Self {
+        let mut path = self.path.clone();
+        path.push(seg);
+        Shared {
+            pipeline: self.pipeline,
+            path,
+        }
+    }
+    /**
+    Read the shared pipeline.
+    */
+    pub fn read<T>(&self, f: impl FnOnce(&Pipeline) -> T) -> T {
+        f(&self.pipeline.lock().unwrap())
+    }
+    /**
+    Log the shared pipeline.
+    */
+    pub fn log(&self) -> Result<()> {
+        self.read(|e| e.log())
+    }
+    /**
+    Replace the owner of this handle in the shared pipeline by the given snapshot,
+    then log the shared pipeline.
+    */
+    pub fn publish<T: Snapshot>(&self, snapshot: &T) -> Result<()> {
+        let mut pipeline = self.pipeline.lock().unwrap();
+        if let Some(node) = locate(&mut pipeline, &self.path) {
+            snapshot.write(node);
+        }
+        pipeline.log()
+    }
+}
+
+/**
+A part of the shared pipeline.
+*/
+#[derive(Debug)]
+pub(super) enum Node<'a> {
+    Pipeline(&'a mut Pipeline),
+    Item(&'a mut StepOrParallel),
+    Step(&'a mut Step),
+    Command(&'a mut Command),
+}
+
+/**
+Follow a path from the pipeline.
+*/
+fn locate<'a>(pipeline: &'a mut Pipeline, path: &[Seg]) -> Option<Node<'a>> {
+    let mut node = Node::Pipeline(pipeline);
+    for seg in path {
+        node = match (node, seg) {
+            (Node::Pipeline(pipeline), Seg::Step(i)) => Node::Item(pipeline.steps.get_mut(*i)?),
+            (Node::Pipeline(pipeline), Seg::Fallback(hook, i)) => {
+                Node::Item(pipeline.fallback.as_mut()?.get_hook(*hook)?.get_mut(*i)?)
+            }
+            (Node::Item(StepOrParallel::Parallel(parallel)), Seg::Step(i)) => {
+                Node::Step(parallel.steps.get_mut(*i)?)
+            }
+            (Node::Item(StepOrParallel::Step(step)), seg) => locate_in_step(step, seg)?,
+            (Node::Step(step), seg) => locate_in_step(step, seg)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+fn locate_in_step<'a>(step: &'a mut Step, seg: &Seg) -> Option<Node<'a>> {
+    match seg {
+        Seg::Fallback(hook, i) => Some(Node::Item(
+            step.fallback.as_mut()?.get_hook(*hook)?.get_mut(*i)?,
+        )),
+        Seg::Command(i) => Some(Node::Command(step.commands.get_mut(*i)?)),
+        Seg::Child => Some(Node::Pipeline(step.child.as_deref_mut()?)),
+        Seg::Step(_) => None,
+    }
+}
+
+/**
+A part of the run that can be published to the shared pipeline.
+*/
+pub(super) trait Snapshot {
+    fn write(&self, node: Node);
+}
+impl Snapshot for Pipeline {
+    fn write(&self, node: Node) {
+        if let Node::Pipeline(e) = node {
+            *e = self.clone();
+        }
+    }
+}
+impl Snapshot for Parallel {
+    fn write(&self, node: Node) {
+        if let Node::Item(StepOrParallel::Parallel(e)) = node {
+            **e = self.clone();
+        }
+    }
+}
+impl Snapshot for Step {
+    fn write(&self, node: Node) {
+        match node {
+            Node::Item(StepOrParallel::Step(e)) => **e = self.clone(),
+            Node::Step(e) => *e = self.clone(),
+            _ => {}
+        }
+    }
+}
+impl Snapshot for Command {
+    fn write(&self, node: Node) {
+        if let Node::Command(e) = node {
+            *e = self.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Hook, Seg, Shared};
+    use crate::types::{Command, Event, Fallback, Parallel, Pipeline, Step, StepOrParallel};
+    use miette::Result;
+    use pipelight_exec::{Statuable, Status};
+    use std::sync::Mutex;
+
+    #[test]
+    fn publish_nested() -> Result<()> {
+        let fallback_step = StepOrParallel::Step(Box::new(Step {
+            commands: vec![Command::new("echo fallback")],
+            ..Default::default()
+        }));
+        let child = Pipeline {
+            steps: vec![StepOrParallel::Parallel(Box::new(Parallel {
+                steps: vec![Step::default(), Step::default()],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        // Logged on every publication
+        let pipeline = Pipeline {
+            name: "test_shared".to_owned(),
+            event: Some(Event::default()),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                child: Some(Box::new(child)),
+                fallback: Some(Fallback {
+                    on_failure: Some(vec![fallback_step]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let shared = Mutex::new(pipeline);
+        let step = Shared::new(&shared).join(Seg::Step(0));
+
+        // A step of a parallel block of the invoked pipeline
+        let mut parallel_step = Step::default();
+        parallel_step.set_status(Some(Status::Running));
+        step.join(Seg::Child)
+            .join(Seg::Step(0))
+            .join(Seg::Step(1))
+            .publish(&parallel_step)?;
+        // A command of a step fallback
+        let mut command = Command::new("echo fallback");
+        command.set_status(Some(Status::Succeeded));
+        step.join(Seg::Fallback(Hook::Failure, 0))
+            .join(Seg::Command(0))
+            .publish(&command)?;
+
+        let pipeline = shared.into_inner().unwrap();
+        let StepOrParallel::Step(step) = &pipeline.steps[0] else {
+            panic!("not a step");
+        };
+        let StepOrParallel::Parallel(parallel) = &step.child.as_ref().unwrap().steps[0] else {
+            panic!("not a parallel block");
+        };
+        assert_eq!(parallel.steps[0].get_status(), None);
+        assert_eq!(parallel.steps[1].get_status(), Some(Status::Running));
+        let fallback = step.fallback.as_ref().unwrap().on_failure.as_ref().unwrap();
+        let StepOrParallel::Step(fallback_step) = &fallback[0] else {
+            panic!("not a step");
+        };
+        assert_eq!(
+            fallback_step.commands[0].get_status(),
+            Some(Status::Succeeded)
+        );
+        Ok(())
+    }
+}
@@ -1,15 +1,27 @@
 #[cfg(test)]
 mod test {
-    use crate::types::{Command, Parallel, Pipeline, Step, StepOrParallel};
-    use miette::Result;
+    use super::super::abort::abort_current;
+    use crate::globals::{LIVE_OUTPUT, SECRETS};
+    use crate::scheduler::JOBS;
+    use crate::types::{
+        Artifacts, Backoff, Command, Concurrency, Event, Fallback, Inputs, Limits, Mode, Parallel,
+        ParallelOpts, Pipeline, PipelineOpts, Retry, Sandbox, Step, StepOpts, StepOrParallel,
+    };
+    use miette::{IntoDiagnostic, Result};
+    use pipelight_exec::{Reason, Statuable, Status};
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use uuid::Uuid;
 
     #[test]
     fn can_run() {
         let mut p = Pipeline {
-            steps: vec![StepOrParallel::Step(Step {
+            steps: vec![StepOrParallel::Step(Box::new(Step {
                 commands: vec![Command::new("echo test")],
                 ..Step::default()
-            })],
+            }))],
             ..Pipeline::default()
         };
         assert!(p.run().is_ok());
@@ -19,11 +31,11 @@ mod test {
     fn run_default() -> Result<()> {
         let mut p = Pipeline {
             name: "test".to_owned(),
-            steps: vec![StepOrParallel::Step(Step {
+            steps: vec![StepOrParallel::Step(Box::new(Step {
                 name: "test".to_owned(),
                 commands: vec![Command::new("sleep 5"), Command::new("pwd")],
                 ..Default::default()
-            })],
+            }))],
             ..Default::default()
         };
         p.run()?;
@@ -34,7 +46,7 @@ mod test {
     fn run_parallel() -> Result<()> {
         let mut p = Pipeline {
             name: "test".to_owned(),
-            steps: vec![StepOrParallel::Parallel(Parallel {
+            steps: vec![StepOrParallel::Parallel(Box::new(Parallel {
                 steps: vec![
                     Step {
                         name: "test".to_owned(),
@@ -48,11 +60,1264 @@ mod test {
                     },
                 ],
                 ..Default::default()
-            })],
+            }))],
             ..Default::default()
         };
         p.run()?;
         println!("{:#?}", p);
         Ok(())
     }
+    #[test]
+    fn run_graph() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_graph".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new("sleep 1")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "lint".to_owned(),
+                    commands: vec![Command::new("false")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "package".to_owned(),
+                    commands: vec![Command::new("pwd")],
+                    needs: Some(vec!["build".to_owned(), "lint".to_owned()]),
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        // Independent steps have run while the dependent one has been held back.
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.steps[1].get_status(), Some(Status::Failed));
+        assert_eq!(p.steps[2].get_status(), None);
+        assert_eq!(p.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_graph_unrelated_branch() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_graph_branch".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "lint".to_owned(),
+                    commands: vec![Command::new("false")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new("sleep 1")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "package".to_owned(),
+                    commands: vec![Command::new("pwd")],
+                    needs: Some(vec!["build".to_owned()]),
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        // The failed step doesn't hold back the branch that doesn't need it.
+        assert_eq!(p.steps[0].get_status(), Some(Status::Failed));
+        assert_eq!(p.steps[2].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_step_timeout() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_step_timeout".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "hang".to_owned(),
+                commands: vec![Command::new("sleep 10"), Command::new("pwd")],
+                timeout: Some("500ms".to_owned()),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        // The step is killed and its timeout fails the pipeline.
+        assert_eq!(p.steps[0].get_status(), Some(Status::TimedOut));
+        assert_eq!(p.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_pipeline_timeout() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_pipeline_timeout".to_owned(),
+            timeout: Some("500ms".to_owned()),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "hang".to_owned(),
+                commands: vec![Command::new("sleep 10")],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[0].get_status(), Some(Status::TimedOut));
+        assert_eq!(p.status, Some(Status::TimedOut));
+        Ok(())
+    }
+    #[test]
+    fn run_retry() -> Result<()> {
+        // Fails on first attempt only
+        let flag = format!("/tmp/pipelight_test_retry_{}", uuid::Uuid::new_v4());
+        let command = format!(
+            "if [ -f {flag} ]; then rm {flag}; else touch {flag}; false; fi",
+            flag = flag
+        );
+        let mut p = Pipeline {
+            name: "test_retry".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "flaky".to_owned(),
+                commands: vec![Command::new(&command)],
+                options: Some(StepOpts {
+                    retry: Some(Retry {
+                        attempts: 3,
+                        delay: Some("10ms".to_owned()),
+                        backoff: Backoff::Exponential,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        let StepOrParallel::Step(step) = &p.steps[0] else {
+            panic!("expected a step");
+        };
+        assert_eq!(step.get_attempt(), 2);
+        assert_eq!(
+            step.attempts.as_ref().unwrap()[0].status,
+            Some(Status::Failed)
+        );
+        assert_eq!(step.status, Some(Status::Succeeded));
+        assert_eq!(p.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_retry_exhausted() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_retry_exhausted".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "broken".to_owned(),
+                commands: vec![Command::new("false")],
+                options: Some(StepOpts {
+                    retry: Some(Retry {
+                        attempts: 3,
+                        delay: None,
+                        backoff: Backoff::Constant,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        let StepOrParallel::Step(step) = &p.steps[0] else {
+            panic!("expected a step");
+        };
+        assert_eq!(step.attempts.as_ref().unwrap().len(), 2);
+        assert_eq!(step.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_condition() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_condition".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new("pwd")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "deploy".to_owned(),
+                    commands: vec![Command::new("pwd")],
+                    condition: Some("steps.build.status == 'failed'".to_owned()),
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "notify".to_owned(),
+                    commands: vec![Command::new("pwd")],
+                    condition: Some(
+                        "steps.deploy.status == 'skipped' && !env.PIPELIGHT_UNSET".to_owned(),
+                    ),
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[1].get_status(), Some(Status::Skipped));
+        assert_eq!(p.steps[2].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_parallel_fail_fast() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_fail_fast".to_owned(),
+            steps: vec![StepOrParallel::Parallel(Box::new(Parallel {
+                steps: vec![
+                    Step {
+                        name: "broken".to_owned(),
+                        commands: vec![Command::new("false")],
+                        ..Default::default()
+                    },
+                    Step {
+                        name: "pending".to_owned(),
+                        commands: vec![Command::new("pwd")],
+                        ..Default::default()
+                    },
+                ],
+                options: Some(ParallelOpts {
+                    max_parallel: Some(1),
+                    fail_fast: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        let StepOrParallel::Parallel(parallel) = &p.steps[0] else {
+            panic!("expected a parallel block");
+        };
+        assert_eq!(parallel.steps[0].status, Some(Status::Failed));
+        assert_eq!(parallel.steps[1].status, Some(Status::Aborted));
+        assert_eq!(p.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_env() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_env".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "env".to_owned(),
+                commands: vec![Command::new("test \"$PIPELIGHT_TEST_LEVEL\" = step")],
+                options: Some(StepOpts {
+                    env: Some(BTreeMap::from([(
+                        "PIPELIGHT_TEST_LEVEL".to_owned(),
+                        "step".to_owned(),
+                    )])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_condition_env() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_condition_env".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "deploy".to_owned(),
+                commands: vec![Command::new("test ${{ env.PIPELIGHT_TEST_TARGET }} = prod")],
+                condition: Some("env.PIPELIGHT_TEST_TARGET == 'prod'".to_owned()),
+                options: Some(StepOpts {
+                    env: Some(BTreeMap::from([(
+                        "PIPELIGHT_TEST_TARGET".to_owned(),
+                        "prod".to_owned(),
+                    )])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        // The step environment is seen by conditions and interpolation.
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_metadata_env() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_metadata".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "metadata".to_owned(),
+                commands: vec![Command::new(
+                    "test \"$PIPELIGHT_PIPELINE/$PIPELIGHT_STEP\" = test_metadata/metadata",
+                )],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.status, Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_cwd() -> Result<()> {
+        let cwd = std::env::current_dir().unwrap().join("src");
+        let mut p = Pipeline {
+            name: "test_cwd".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "cwd".to_owned(),
+                commands: vec![Command::new("pwd")],
+                options: Some(StepOpts {
+                    cwd: Some(cwd.display().to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        let StepOrParallel::Step(step) = &p.steps[0] else {
+            panic!("expected a step");
+        };
+        let process = &step.commands[0].process;
+        assert_eq!(
+            process.io.stdout.as_deref().map(str::trim),
+            Some(cwd.display().to_string().as_str())
+        );
+        assert_eq!(process.cwd, Some(cwd.display().to_string()));
+        Ok(())
+    }
+    #[test]
+    fn run_artifacts() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_artifacts".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "build".to_owned(),
+                commands: vec![Command::new(
+                    "mkdir -p .pipelight/test_build && echo app > .pipelight/test_build/app",
+                )],
+                artifacts: Some(Artifacts {
+                    globs: vec![".pipelight/test_build/*".to_owned()],
+                    files: None,
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        let steps = p.get_artifact_steps();
+        assert_eq!(
+            steps[0].artifacts.as_ref().unwrap().files,
+            Some(vec![".pipelight/test_build/app".to_owned()])
+        );
+        let dir = steps[0].get_artifacts_dir(&p.uuid);
+        assert!(dir.join(".pipelight/test_build/app").is_file());
+
+        // Removed along the logs
+        p.clean()?;
+        assert!(!p.get_artifacts_dir().exists());
+        Ok(())
+    }
+    #[test]
+    fn run_cache() -> Result<()> {
+        let dir = ".pipelight/test_cache";
+        std::fs::create_dir_all(dir).unwrap();
+        // Unique input content so that previous test runs never hit
+        std::fs::write(format!("{}/input", dir), uuid::Uuid::new_v4().to_string()).unwrap();
+        std::fs::write(format!("{}/count", dir), "").unwrap();
+
+        let pipeline = Pipeline {
+            name: "test_cache".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "build".to_owned(),
+                commands: vec![Command::new(&format!(
+                    "echo run >> {dir}/count && cp {dir}/input {dir}/output"
+                ))],
+                inputs: Some(Inputs {
+                    globs: vec![format!("{}/input", dir)],
+                    ..Default::default()
+                }),
+                artifacts: Some(Artifacts {
+                    globs: vec![format!("{}/output", dir)],
+                    files: None,
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let get_inputs = |p: &Pipeline| {
+            let StepOrParallel::Step(step) = &p.steps[0] else {
+                panic!("expected a step");
+            };
+            step.inputs.clone().unwrap()
+        };
+
+        // Cache miss
+        let mut first = pipeline.clone();
+        first.run()?;
+        assert_eq!(first.status, Some(Status::Succeeded));
+        assert_eq!(get_inputs(&first).cached, Some(false));
+
+        // Cache hit: the output is restored without running the commands
+        std::fs::remove_file(format!("{}/output", dir)).unwrap();
+        // Renamed: runs of a homologous pipeline would be blocked
+        let mut second = Pipeline {
+            name: "test_cache_hit".to_owned(),
+            uuid: uuid::Uuid::new_v4(),
+            ..pipeline.clone()
+        };
+        second.run()?;
+        assert_eq!(second.status, Some(Status::Succeeded));
+        assert_eq!(get_inputs(&second).cached, Some(true));
+        assert_eq!(get_inputs(&second).key, get_inputs(&first).key);
+        assert!(std::path::Path::new(&format!("{}/output", dir)).is_file());
+        let count = std::fs::read_to_string(format!("{}/count", dir)).unwrap();
+        assert_eq!(count.lines().count(), 1);
+
+        first.clean()?;
+        second.clean()?;
+        Ok(())
+    }
+    #[test]
+    fn run_cache_outputs() -> Result<()> {
+        let dir = ".pipelight/test_cache_outputs";
+        std::fs::create_dir_all(dir).unwrap();
+        // Unique input content so that previous test runs never hit
+        std::fs::write(format!("{}/input", dir), uuid::Uuid::new_v4().to_string()).unwrap();
+
+        let pipeline = |name: &str| Pipeline {
+            name: name.to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "version".to_owned(),
+                    commands: vec![Command::new("echo tag=v1.2.0 >> $PIPELIGHT_OUTPUT")],
+                    inputs: Some(Inputs {
+                        globs: vec![format!("{}/input", dir)],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "release".to_owned(),
+                    commands: vec![Command::new(
+                        "test \"${{ steps.version.outputs.tag }}\" = v1.2.0",
+                    )],
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        let mut first = pipeline("test_cache_outputs");
+        first.run()?;
+        assert_eq!(first.status, Some(Status::Succeeded));
+
+        // Cache hit: the outputs are restored for the next steps
+        let mut second = pipeline("test_cache_outputs_hit");
+        second.run()?;
+        let StepOrParallel::Step(step) = &second.steps[0] else {
+            panic!("expected a step");
+        };
+        assert_eq!(step.inputs.as_ref().unwrap().cached, Some(true));
+        assert_eq!(
+            step.outputs.clone().unwrap().get("tag"),
+            Some(&"v1.2.0".to_owned())
+        );
+        assert_eq!(second.steps[1].get_status(), Some(Status::Succeeded));
+
+        first.clean()?;
+        second.clean()?;
+        Ok(())
+    }
+    #[test]
+    fn run_cache_key() -> Result<()> {
+        let step = Step {
+            name: "release".to_owned(),
+            commands: vec![Command::new("echo ${{ steps.version.outputs.tag }}")],
+            inputs: Some(Inputs::default()),
+            ..Default::default()
+        };
+        let pipeline = |tag: &str| Pipeline {
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "version".to_owned(),
+                outputs: Some(BTreeMap::from([("tag".to_owned(), tag.to_owned())])),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        // Keyed by the values the commands get, not by the placeholders
+        let key = step.get_cache_key(&pipeline("v1.2.0"))?;
+        assert!(key.is_some());
+        assert_eq!(key, step.get_cache_key(&pipeline("v1.2.0"))?);
+        assert_ne!(key, step.get_cache_key(&pipeline("v1.3.0"))?);
+        // Never cached when a placeholder can't be resolved
+        assert_eq!(step.get_cache_key(&Pipeline::default())?, None);
+        Ok(())
+    }
+    #[test]
+    fn run_parallel_stop_on_failure() -> Result<()> {
+        // Steps must run concurrently, whatever the number of cores
+        JOBS.set_limit(Some(4));
+        let mut p = Pipeline {
+            name: "test_parallel_stop".to_owned(),
+            steps: vec![StepOrParallel::Parallel(Box::new(Parallel {
+                steps: vec![
+                    Step {
+                        name: "slow".to_owned(),
+                        commands: vec![Command::new("sleep 5")],
+                        ..Default::default()
+                    },
+                    Step {
+                        name: "failing".to_owned(),
+                        commands: vec![Command::new("sleep 0.2 && false")],
+                        ..Default::default()
+                    },
+                ],
+                options: Some(ParallelOpts {
+                    mode: Some(Mode::StopOnFailure),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        p.run()?;
+        assert!(start.elapsed() < std::time::Duration::from_secs(4));
+
+        let StepOrParallel::Parallel(parallel) = &p.steps[0] else {
+            panic!("expected a parallel block");
+        };
+        assert_eq!(parallel.steps[0].status, Some(Status::Aborted));
+        assert_eq!(
+            parallel.steps[0].commands[0].get_status(),
+            Some(Status::Aborted)
+        );
+        assert_eq!(parallel.steps[1].status, Some(Status::Failed));
+        assert_eq!(parallel.status, Some(Status::Failed));
+        Ok(())
+    }
+    #[test]
+    fn run_parallel_continue_on_failure() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_parallel_continue".to_owned(),
+            steps: vec![
+                StepOrParallel::Parallel(Box::new(Parallel {
+                    steps: vec![
+                        Step {
+                            name: "failing".to_owned(),
+                            commands: vec![Command::new("false")],
+                            ..Default::default()
+                        },
+                        Step {
+                            name: "passing".to_owned(),
+                            commands: vec![Command::new("sleep 0.2")],
+                            ..Default::default()
+                        },
+                    ],
+                    options: Some(ParallelOpts {
+                        mode: Some(Mode::ContinueOnFailure),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "next".to_owned(),
+                    commands: vec![Command::new("pwd")],
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        let StepOrParallel::Parallel(parallel) = &p.steps[0] else {
+            panic!("expected a parallel block");
+        };
+        assert_eq!(parallel.steps[1].status, Some(Status::Succeeded));
+        assert_eq!(parallel.status, Some(Status::Failed));
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_abort() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_abort".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "long".to_owned(),
+                    commands: vec![Command::new("sleep 10")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "next".to_owned(),
+                    commands: vec![Command::new("echo next")],
+                    ..Default::default()
+                })),
+            ],
+            fallback: Some(Fallback {
+                on_abortion: Some(vec![StepOrParallel::Step(Box::new(Step {
+                    name: "cleanup".to_owned(),
+                    commands: vec![Command::new("echo cleanup")],
+                    ..Default::default()
+                }))]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let aborter = thread::spawn(|| {
+            thread::sleep(Duration::from_secs(1));
+            abort_current();
+        });
+        let start = Instant::now();
+        p.run()?;
+        aborter.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(8));
+
+        assert_eq!(p.get_status(), Some(Status::Aborted));
+        assert_eq!(p.steps[0].get_status(), Some(Status::Aborted));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            assert_eq!(step.commands[0].get_status(), Some(Status::Aborted));
+        }
+        assert_eq!(p.steps[1].get_status(), None);
+        let on_abortion = p.fallback.unwrap().on_abortion.unwrap();
+        assert_eq!(on_abortion[0].get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_abort_parallel_fallback() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_abort_parallel".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "long".to_owned(),
+                commands: vec![Command::new("sleep 10")],
+                ..Default::default()
+            }))],
+            fallback: Some(Fallback {
+                on_abortion: Some(vec![StepOrParallel::Parallel(Box::new(Parallel {
+                    steps: vec![
+                        Step {
+                            name: "cleanup_a".to_owned(),
+                            commands: vec![Command::new("echo a")],
+                            ..Default::default()
+                        },
+                        Step {
+                            name: "cleanup_b".to_owned(),
+                            commands: vec![Command::new("echo b")],
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                }))]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let aborter = thread::spawn(|| {
+            thread::sleep(Duration::from_secs(1));
+            abort_current();
+        });
+        p.run()?;
+        aborter.join().unwrap();
+
+        assert_eq!(p.get_status(), Some(Status::Aborted));
+        // The parallel fallback block runs despite the abortion
+        let on_abortion = p.fallback.unwrap().on_abortion.unwrap();
+        assert_eq!(on_abortion[0].get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_lifecycle_fallbacks() -> Result<()> {
+        let echo = |name: &str, command: &str| {
+            StepOrParallel::Step(Box::new(Step {
+                name: name.to_owned(),
+                commands: vec![Command::new(command)],
+                ..Default::default()
+            }))
+        };
+        let mut p = Pipeline {
+            name: "test_lifecycle".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "main".to_owned(),
+                commands: vec![Command::new("echo main")],
+                fallback: Some(Fallback {
+                    on_started: Some(vec![echo("step_started", "echo started")]),
+                    on_finished: Some(vec![echo("step_finished", "echo finished")]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            fallback: Some(Fallback {
+                on_started: Some(vec![echo("started", "echo started")]),
+                on_finished: Some(vec![
+                    echo("release", "false"),
+                    echo("cleanup", "echo cleanup"),
+                ]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        p.run()?;
+
+        // A failing on_finished hook doesn't fail the pipeline
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        let fallback = p.fallback.unwrap();
+        assert_eq!(
+            fallback.on_started.unwrap()[0].get_status(),
+            Some(Status::Succeeded)
+        );
+        let on_finished = fallback.on_finished.unwrap();
+        assert_eq!(on_finished[0].get_status(), Some(Status::Failed));
+        assert_eq!(on_finished[1].get_status(), Some(Status::Succeeded));
+        assert_eq!(fallback.on_finished_status, Some(Status::Failed));
+
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            let fallback = step.fallback.clone().unwrap();
+            assert_eq!(
+                fallback.on_started.unwrap()[0].get_status(),
+                Some(Status::Succeeded)
+            );
+            assert_eq!(fallback.on_finished_status, Some(Status::Succeeded));
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_pipeline_step() -> Result<()> {
+        let build = Pipeline {
+            name: "test_build".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "compile".to_owned(),
+                commands: vec![Command::new("echo compile")],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let mut p = Pipeline {
+            name: "test_release".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![],
+                    pipeline: Some("test_build".to_owned()),
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "publish".to_owned(),
+                    commands: vec![Command::new("echo publish")],
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.link_pipelines(&[build.clone(), p.clone()], &mut vec![]);
+        p.run()?;
+
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            assert_eq!(step.get_status(), Some(Status::Succeeded));
+            let child = step.child.clone().unwrap();
+            assert_ne!(child.uuid, build.uuid);
+            assert_eq!(child.get_status(), Some(Status::Succeeded));
+            assert_eq!(child.steps[0].get_status(), Some(Status::Succeeded));
+        }
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_pipeline_step_empty() -> Result<()> {
+        let empty = Pipeline {
+            name: "test_empty".to_owned(),
+            steps: vec![],
+            ..Default::default()
+        };
+        let mut p = Pipeline {
+            name: "test_invoke_empty".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "empty".to_owned(),
+                commands: vec![],
+                pipeline: Some("test_empty".to_owned()),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.link_pipelines(&[empty, p.clone()], &mut vec![]);
+        p.run()?;
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+
+    #[test]
+    fn run_resume() -> Result<()> {
+        let steps = |second: &str| {
+            vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "prepare".to_owned(),
+                    commands: vec![Command::new("echo prepare")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "deploy".to_owned(),
+                    commands: vec![Command::new(second)],
+                    ..Default::default()
+                })),
+            ]
+        };
+        let mut previous = Pipeline {
+            name: "test_resume".to_owned(),
+            steps: steps("false"),
+            ..Default::default()
+        };
+        previous.run()?;
+        assert_eq!(previous.get_status(), Some(Status::Failed));
+
+        let mut p = Pipeline {
+            name: "test_resume".to_owned(),
+            steps: steps("echo deploy"),
+            ..Default::default()
+        };
+        p.resume(&previous, None)?;
+        p.run()?;
+
+        assert_eq!(p.origin, Some(previous.uuid));
+        assert!(p.steps[0].is_reused());
+        assert!(!p.steps[1].is_reused());
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+
+        // Can't resume from an unknown step
+        let mut p = Pipeline {
+            name: "test_resume".to_owned(),
+            steps: steps("echo deploy"),
+            ..Default::default()
+        };
+        assert!(p.resume(&previous, Some("unknown")).is_err());
+        Ok(())
+    }
+    #[test]
+    fn run_resume_artifacts() -> Result<()> {
+        let steps = |second: &str| {
+            vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new(
+                        "mkdir -p .pipelight/test_resume_build && echo app > .pipelight/test_resume_build/app",
+                    )],
+                    artifacts: Some(Artifacts {
+                        globs: vec![".pipelight/test_resume_build/*".to_owned()],
+                        files: None,
+                    }),
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "deploy".to_owned(),
+                    commands: vec![Command::new(second)],
+                    ..Default::default()
+                })),
+            ]
+        };
+        let mut previous = Pipeline {
+            name: "test_resume_artifacts".to_owned(),
+            steps: steps("false"),
+            ..Default::default()
+        };
+        previous.run()?;
+
+        let mut p = Pipeline {
+            name: "test_resume_artifacts".to_owned(),
+            steps: steps("echo deploy"),
+            ..Default::default()
+        };
+        p.resume(&previous, None)?;
+        let steps = p.get_artifact_steps();
+        let dir = steps[0].get_artifacts_dir(&p.uuid);
+        // Resuming has no side effect until the run starts
+        assert!(!dir.exists());
+
+        p.run()?;
+        assert!(p.steps[0].is_reused());
+        assert!(dir.join(".pipelight/test_resume_build/app").is_file());
+
+        previous.clean()?;
+        p.clean()?;
+        Ok(())
+    }
+
+    /**
+    Log a fake running instance of the pipeline, owned by this process.
+    */
+    fn fake_running(name: &str) -> Result<Pipeline> {
+        let mut running = Pipeline {
+            name: name.to_owned(),
+            event: Some(Event::default()),
+            ..Default::default()
+        };
+        running.set_status(Some(Status::Running));
+        running.log()?;
+        Ok(running)
+    }
+    #[test]
+    fn run_concurrency_skip() -> Result<()> {
+        fake_running("test_concurrency_skip")?;
+        let mut p = Pipeline {
+            name: "test_concurrency_skip".to_owned(),
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.get_status(), Some(Status::Skipped));
+        assert_eq!(p.steps[0].get_status(), None);
+        Ok(())
+    }
+    #[test]
+    fn run_concurrency_allow() -> Result<()> {
+        fake_running("test_concurrency_allow")?;
+        let mut p = Pipeline {
+            name: "test_concurrency_allow".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                commands: vec![Command::new("echo test")],
+                ..Default::default()
+            }))],
+            options: Some(PipelineOpts {
+                concurrency: Some(Concurrency::Allow),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_concurrency_queue() -> Result<()> {
+        let mut running = fake_running("test_concurrency_queue")?;
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            running.set_status(Some(Status::Succeeded));
+            running.log().unwrap();
+        });
+        let mut p = Pipeline {
+            name: "test_concurrency_queue".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                commands: vec![Command::new("echo test")],
+                ..Default::default()
+            }))],
+            options: Some(PipelineOpts {
+                concurrency: Some(Concurrency::Queue),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let start = Instant::now();
+        p.run()?;
+        handle.join().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        Ok(())
+    }
+    #[test]
+    fn run_concurrency_queue_waiters() -> Result<()> {
+        let name = "test_concurrency_queue_waiters";
+        let mut running = fake_running(name)?;
+        // Each waiter holds the turn for a while once it gets it
+        let wait = |delay: u64| {
+            thread::spawn(move || -> (Instant, Instant) {
+                thread::sleep(Duration::from_millis(delay));
+                let mut p = Pipeline {
+                    name: name.to_owned(),
+                    options: Some(PipelineOpts {
+                        concurrency: Some(Concurrency::Queue),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+                assert!(p.wait_turn().unwrap());
+                let start = Instant::now();
+                p.set_status(Some(Status::Running));
+                p.log().unwrap();
+                thread::sleep(Duration::from_millis(500));
+                p.set_status(Some(Status::Succeeded));
+                p.log().unwrap();
+                (start, Instant::now())
+            })
+        };
+        let first = wait(0);
+        let second = wait(100);
+        thread::sleep(Duration::from_millis(300));
+        running.set_status(Some(Status::Succeeded));
+        running.log()?;
+
+        let first = first.join().unwrap();
+        let second = second.join().unwrap();
+        // The waiters didn't start at once, and kept their queue order
+        assert!(second.0 >= first.1);
+        Ok(())
+    }
+
+    #[test]
+    fn run_outputs() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_outputs".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "version".to_owned(),
+                    commands: vec![Command::new("echo tag=v1.2.0 >> $PIPELIGHT_OUTPUT")],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "release".to_owned(),
+                    commands: vec![Command::new(
+                        "test \"${{ steps.version.outputs.tag }}\" = v1.2.0",
+                    )],
+                    condition: Some("steps.version.outputs.tag == 'v1.2.0'".to_owned()),
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "hotfix".to_owned(),
+                    commands: vec![Command::new("echo hotfix")],
+                    condition: Some("steps.version.outputs.tag == 'v1.1.9'".to_owned()),
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            assert_eq!(
+                step.outputs.clone().unwrap().get("tag"),
+                Some(&"v1.2.0".to_owned())
+            );
+        }
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.steps[2].get_status(), Some(Status::Skipped));
+        Ok(())
+    }
+
+    #[test]
+    fn run_outputs_env() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_outputs_env".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "version".to_owned(),
+                    commands: vec![Command::new(
+                        "echo 'tag=a  b;$(exit 1)' >> $PIPELIGHT_OUTPUT",
+                    )],
+                    ..Default::default()
+                })),
+                // Passed as is, never read as shell code
+                StepOrParallel::Step(Box::new(Step {
+                    name: "release".to_owned(),
+                    commands: vec![
+                        Command::new(
+                            "test \"${{ steps.version.outputs.tag }}\" = 'a  b;$(exit 1)'",
+                        ),
+                        Command::new("test ${{ steps.version.outputs.tag }} = 'a  b;$(exit 1)'"),
+                    ],
+                    ..Default::default()
+                })),
+                StepOrParallel::Step(Box::new(Step {
+                    name: "unresolved".to_owned(),
+                    commands: vec![Command::new("echo ${{ steps.version.outputs.none }}")],
+                    options: Some(StepOpts {
+                        mode: Some(Mode::JumpNextOnFailure),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        assert_eq!(p.steps[2].get_status(), Some(Status::Failed));
+        if let StepOrParallel::Step(step) = &p.steps[2] {
+            let stderr = step.commands[0].process.io.stderr.clone().unwrap();
+            assert!(stderr.contains("Couldn't resolve ${{ steps.version.outputs.none }}"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn run_secrets() -> Result<()> {
+        SECRETS
+            .lock()
+            .unwrap()
+            .insert("TEST_DEPLOY_TOKEN".to_owned(), "d3pl0y".to_owned());
+        let mut p = Pipeline {
+            name: "test_secrets".to_owned(),
+            steps: vec![
+                StepOrParallel::Step(Box::new(Step {
+                    name: "deploy".to_owned(),
+                    commands: vec![
+                        Command::new("echo $TEST_DEPLOY_TOKEN"),
+                        Command::new("echo ${{ secrets.TEST_DEPLOY_TOKEN }}"),
+                    ],
+                    ..Default::default()
+                })),
+                // Not exported to the steps that don't reference it
+                StepOrParallel::Step(Box::new(Step {
+                    name: "build".to_owned(),
+                    commands: vec![Command::new("test -z \"$TEST_DEPLOY_TOKEN\"")],
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        assert_eq!(p.steps[1].get_status(), Some(Status::Succeeded));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            for command in &step.commands {
+                assert_eq!(command.process.io.stdout, Some("***\n".to_owned()));
+            }
+            // Referenced through the environment
+            assert_eq!(
+                step.commands[1].process.io.stdin,
+                Some("echo \"$TEST_DEPLOY_TOKEN\"".to_owned())
+            );
+        }
+        // Nothing leaks into the log file
+        let log =
+            fs::read_to_string(format!(".pipelight/logs/{}.json", p.uuid)).into_diagnostic()?;
+        assert!(!log.contains("d3pl0y"));
+        Ok(())
+    }
+
+    #[test]
+    fn run_secrets_argv() -> Result<()> {
+        SECRETS
+            .lock()
+            .unwrap()
+            .insert("TEST_ARGV_TOKEN".to_owned(), "4rgv".to_owned());
+        let argv = std::env::temp_dir().join(format!("pipelight_argv_{}", Uuid::new_v4()));
+        let mut p = Pipeline {
+            name: "test_secrets_argv".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "deploy".to_owned(),
+                commands: vec![Command::new(&format!(
+                    "test -n ${{{{ secrets.TEST_ARGV_TOKEN }}}} && tr '\\0' ' ' < /proc/$$/cmdline > {}",
+                    argv.display()
+                ))],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+        assert_eq!(p.get_status(), Some(Status::Succeeded));
+        // The shell arguments hold the reference, not the value
+        let cmdline = fs::read_to_string(&argv).into_diagnostic()?;
+        fs::remove_file(&argv).into_diagnostic()?;
+        assert!(cmdline.contains("\"$TEST_ARGV_TOKEN\""));
+        assert!(!cmdline.contains("4rgv"));
+        Ok(())
+    }
+
+    #[test]
+    fn run_sandbox() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_sandbox".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "isolated".to_owned(),
+                commands: vec![
+                    Command::new("touch Cargo.toml || echo result=read-only >> $PIPELIGHT_OUTPUT"),
+                    Command::new("echo pid=$$ >> $PIPELIGHT_OUTPUT"),
+                ],
+                options: Some(StepOpts {
+                    sandbox: Some(Sandbox::default()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            let outputs = step.outputs.clone().unwrap();
+            assert_eq!(outputs.get("result"), Some(&"read-only".to_owned()));
+            assert_eq!(outputs.get("pid"), Some(&"1".to_owned()));
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_limits() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_limits".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "runaway".to_owned(),
+                commands: vec![Command::new("while true; do :; done")],
+                options: Some(StepOpts {
+                    limits: Some(Limits {
+                        cpu_time: Some(1),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        p.run()?;
+
+        assert_eq!(p.steps[0].get_status(), Some(Status::Failed));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            assert_eq!(
+                step.commands[0].process.state.reason,
+                Some(Reason::CpuTimeLimit)
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_live_output() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_live_output".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "print".to_owned(),
+                commands: vec![Command::new("echo one; echo two")],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        *LIVE_OUTPUT.lock().unwrap() = true;
+        let res = p.run();
+        *LIVE_OUTPUT.lock().unwrap() = false;
+        res?;
+
+        // Streamed outputs are still collected
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            assert_eq!(
+                step.commands[0].process.io.stdout,
+                Some("one\ntwo\n".to_owned())
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn run_background_child() -> Result<()> {
+        let mut p = Pipeline {
+            name: "test_background_child".to_owned(),
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "serve".to_owned(),
+                commands: vec![Command::new("sleep 4 & echo started")],
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let start = Instant::now();
+        p.run()?;
+        // The step doesn't wait for the backgrounded child
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(p.steps[0].get_status(), Some(Status::Succeeded));
+        if let StepOrParallel::Step(step) = &p.steps[0] {
+            assert_eq!(
+                step.commands[0].process.io.stdout,
+                Some("started\n".to_owned())
+            );
+        }
+        Ok(())
+    }
 }
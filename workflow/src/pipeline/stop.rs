@@ -3,22 +3,100 @@ use crate::types::{Pipeline, Status};
 // Error Handling
 use miette::{IntoDiagnostic, Result};
 // Unix process manipiulation
-use rustix::process::{kill_process_group, Signal};
+use pipelight_exec::Finder;
+use rustix::process::{kill_process, kill_process_group, Pid, Signal};
 
-/**
-Abort process execution
-Kil the process group
-*/
 impl Pipeline {
+    /**
+    Abort process execution, of a running or queued run:
+    kill the process group,
+    and the process groups of commands that run in their own (timed commands).
+    */
     pub fn stop(&mut self) -> Result<()> {
-        if self.event.is_some() && self.status == Some(Status::Running) {
-            let _pid = self.clone().event.unwrap().pid.unwrap();
-            let pgid_raw = self.event.clone().unwrap().pgid.unwrap();
-            let pgid = rustix::process::Pid::from_raw(pgid_raw).unwrap();
-            kill_process_group(pgid, Signal::Term).into_diagnostic()?;
-            self.status = Some(Status::Aborted);
-            self.log()?;
+        if !matches!(self.status, Some(Status::Running) | Some(Status::Started)) {
+            return Ok(());
         }
+        let Some(event) = self.event.clone() else {
+            return Ok(());
+        };
+        // Nothing to signal for a run whose process is gone
+        if self.is_running()? {
+            // The pipelight process first, so that it aborts its steps
+            // before it sees their commands killed.
+            if let Some(pgid) = event.pgid.and_then(Pid::from_raw) {
+                kill_process_group(pgid, Signal::Term).into_diagnostic()?;
+            }
+            self.kill_commands()?;
+        }
+        self.status = Some(Status::Aborted);
+        self.log()?;
+        Ok(())
+    }
+    /**
+    Gracefully abort a run from another process of the same process group:
+    signal the pipelight process and the process groups of its commands,
+    but not the shared group.
+    The aborted run logs its own final state.
+    */
+    pub fn cancel(&self) -> Result<()> {
+        if let Some(pid) = self
+            .event
+            .as_ref()
+            .and_then(|e| e.pid.and_then(Pid::from_raw))
+        {
+            kill_process(pid, Signal::Term).into_diagnostic()?;
+            self.kill_commands()?;
+        }
+        Ok(())
+    }
+    /**
+    Kill the process groups of the commands that run in their own.
+    */
+    fn kill_commands(&self) -> Result<()> {
+        let Some(pid) = self.event.as_ref().and_then(|e| e.pid) else {
+            return Ok(());
+        };
+        let finder = Finder::new().ppid(&(pid as u32)).search()?;
+        if let Some(children) = finder.matches {
+            for child in children {
+                if let Some(child_pgid) = child.pid.and_then(Pid::from_raw) {
+                    kill_process_group(child_pgid, Signal::Term).ok();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Event, Pipeline, Status};
+    use miette::{IntoDiagnostic, Result};
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process::Command;
+
+    #[test]
+    fn stop_queued() -> Result<()> {
+        // A run waiting for its turn, in its own process group
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .into_diagnostic()?;
+        let pid = child.id() as i32;
+        let mut pipeline = Pipeline {
+            name: "test_stop_queued".to_owned(),
+            status: Some(Status::Started),
+            event: Some(Event {
+                pid: Some(pid),
+                pgid: Some(pid),
+                ..Event::default()
+            }),
+            ..Default::default()
+        };
+        pipeline.stop()?;
+        assert_eq!(child.wait().into_diagnostic()?.signal(), Some(15));
+        assert_eq!(pipeline.status, Some(Status::Aborted));
         Ok(())
     }
 }
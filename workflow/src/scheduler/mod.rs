@@ -0,0 +1,141 @@
+// Tests
+mod test;
+// Threads
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+// Global var
+use once_cell::sync::Lazy;
+// Error Handling
+use miette::Result;
+
+/**
+Global pool of job slots.
+Bounds the number of steps running at the same time in this process,
+nested parallel blocks included.
+A pipeline run is its own process, so the bound applies per run.
+*/
+pub static JOBS: Lazy<Jobs> = Lazy::new(Jobs::default);
+
+/**
+A counting semaphore.
+The limit defaults to the number of available cores.
+*/
+#[derive(Debug)]
+pub struct Jobs {
+    limit: Mutex<usize>,
+    running: Mutex<usize>,
+    freed: Condvar,
+}
+impl Default for Jobs {
+    fn default() -> Self {
+        Jobs {
+            limit: Mutex::new(default_limit()),
+            running: Mutex::new(0),
+            freed: Condvar::new(),
+        }
+    }
+}
+
+fn default_limit() -> usize {
+    thread::available_parallelism()
+        .map(|e| e.get())
+        .unwrap_or(1)
+}
+
+impl Jobs {
+    /**
+    Set the maximum number of running jobs.
+    None restores the default limit.
+    */
+    pub fn set_limit(&self, limit: Option<usize>) {
+        *self.limit.lock().unwrap() = limit.unwrap_or_else(default_limit).max(1);
+        self.freed.notify_all();
+    }
+    pub fn get_limit(&self) -> usize {
+        *self.limit.lock().unwrap()
+    }
+    /**
+    Block until a job slot is free and take it.
+    The slot is released when the returned guard is dropped.
+    */
+    pub fn acquire(&self) -> Job<'_> {
+        let mut running = self.running.lock().unwrap();
+        while *running >= self.get_limit() {
+            running = self.freed.wait(running).unwrap();
+        }
+        *running += 1;
+        Job { jobs: self }
+    }
+    fn release(&self) {
+        *self.running.lock().unwrap() -= 1;
+        self.freed.notify_one();
+    }
+}
+
+/**
+A taken job slot.
+*/
+#[derive(Debug)]
+pub struct Job<'a> {
+    jobs: &'a Jobs,
+}
+impl Drop for Job<'_> {
+    fn drop(&mut self) {
+        self.jobs.release();
+    }
+}
+
+/**
+Run a task on every item of a slice,
+with at most max_parallel tasks at the same time.
+*/
+#[derive(Debug, Default, Clone)]
+pub struct Scheduler {
+    pub max_parallel: Option<usize>,
+}
+impl Scheduler {
+    pub fn new(max_parallel: Option<usize>) -> Self {
+        Scheduler { max_parallel }
+    }
+    /**
+    Run the task on every item, on dedicated threads.
+    Items are launched in order, the task gets the item index.
+    When a task returns false, no other item is launched.
+    */
+    pub fn for_each<T, F>(&self, items: &mut [T], task: F) -> Result<()>
+    where
+        T: Send,
+        F: Fn(usize, &mut T) -> Result<bool> + Sync,
+    {
+        let len = items.len();
+        let workers = self.max_parallel.unwrap_or(len).clamp(1, len.max(1));
+
+        // Every item is taken by a single thread.
+        let next = Mutex::new(items.iter_mut().enumerate());
+        let stopped = AtomicBool::new(false);
+        thread::scope(|s| -> Result<()> {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    s.spawn(|| -> Result<()> {
+                        loop {
+                            if stopped.load(Ordering::SeqCst) {
+                                return Ok(());
+                            }
+                            let Some((i, item)) = next.lock().unwrap().next() else {
+                                return Ok(());
+                            };
+                            if !task(i, item)? {
+                                stopped.store(true, Ordering::SeqCst);
+                            }
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap()?;
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod scheduler {
+    use crate::scheduler::{Jobs, Scheduler};
+    use miette::Result;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    /**
+    Run tasks and return the highest number of tasks running at the same time.
+    */
+    fn max_running(scheduler: &Scheduler, jobs: Option<&Jobs>) -> Result<usize> {
+        let running = AtomicUsize::new(0);
+        let max = AtomicUsize::new(0);
+        let mut items = vec![0; 8];
+        scheduler.for_each(&mut items, |_, item| {
+            let _job = jobs.map(|e| e.acquire());
+            let n = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(n, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            *item += 1;
+            Ok(true)
+        })?;
+        assert!(items.iter().all(|e| *e == 1));
+        Ok(max.load(Ordering::SeqCst))
+    }
+
+    #[test]
+    fn max_parallel() -> Result<()> {
+        assert!(max_running(&Scheduler::new(Some(2)), None)? <= 2);
+        assert!(max_running(&Scheduler::new(None), None)? > 2);
+        Ok(())
+    }
+    #[test]
+    fn max_jobs() -> Result<()> {
+        let jobs = Jobs::default();
+        jobs.set_limit(Some(3));
+        assert!(max_running(&Scheduler::new(None), Some(&jobs))? <= 3);
+        Ok(())
+    }
+    #[test]
+    fn stop_launching() -> Result<()> {
+        let mut items = vec![0; 8];
+        Scheduler::new(Some(1)).for_each(&mut items, |_, item| {
+            *item += 1;
+            Ok(false)
+        })?;
+        assert_eq!(items.iter().sum::<i32>(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+// Structs
+use crate::types::{Fallback, Pipeline, Step, StepOrParallel};
+// Filesystem
+use glob::glob;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use uuid::Uuid;
+// Error Handling
+use miette::{Error, IntoDiagnostic, Result};
+
+/**
+Directory that contains the artifacts of every pipeline run.
+*/
+pub static ARTIFACTS_DIR: &str = ".pipelight/artifacts";
+
+fn get_run_artifacts_dir(uuid: &Uuid) -> PathBuf {
+    Path::new(ARTIFACTS_DIR).join(uuid.to_string())
+}
+
+impl Pipeline {
+    /**
+    Return the directory that contains the run artifacts:
+    .pipelight/artifacts/<pipeline-uuid>
+    */
+    pub fn get_artifacts_dir(&self) -> PathBuf {
+        get_run_artifacts_dir(&self.uuid)
+    }
+    /**
+    Return the steps that collected artifacts, in execution order.
+    */
+    pub fn get_artifact_steps(&self) -> Vec<Step> {
+        let mut steps = vec![];
+        for step in &self.steps {
+            steps.extend(step.get_artifact_steps());
+        }
+        if let Some(fallback) = &self.fallback {
+            steps.extend(fallback.get_artifact_steps());
+        }
+        steps
+    }
+}
+impl StepOrParallel {
+    fn get_artifact_steps(&self) -> Vec<Step> {
+        match self {
+            StepOrParallel::Step(step) => step.get_artifact_steps(),
+            StepOrParallel::Parallel(parallel) => {
+                let mut steps = vec![];
+                for step in &parallel.steps {
+                    steps.extend(step.get_artifact_steps());
+                }
+                if let Some(fallback) = &parallel.fallback {
+                    steps.extend(fallback.get_artifact_steps());
+                }
+                steps
+            }
+        }
+    }
+}
+impl Fallback {
+    fn get_artifact_steps(&self) -> Vec<Step> {
+        let mut steps = vec![];
+        for step_or_parallel in [
+            &self.on_started,
+            &self.on_failure,
+            &self.on_success,
+            &self.on_abortion,
+            &self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        {
+            steps.extend(step_or_parallel.get_artifact_steps());
+        }
+        steps
+    }
+}
+
+impl Step {
+    fn get_artifact_steps(&self) -> Vec<Step> {
+        let mut steps = vec![];
+        if self.artifacts.as_ref().is_some_and(|e| e.files.is_some()) {
+            steps.push(self.clone());
+        }
+        if let Some(fallback) = &self.fallback {
+            steps.extend(fallback.get_artifact_steps());
+        }
+        steps
+    }
+    /**
+    Return the directory that contains the step artifacts:
+    .pipelight/artifacts/<pipeline-uuid>/<step>
+    */
+    pub fn get_artifacts_dir(&self, uuid: &Uuid) -> PathBuf {
+        get_run_artifacts_dir(uuid).join(self.name.replace(std::path::MAIN_SEPARATOR, "_"))
+    }
+    /**
+    Copy the files matching the declared globs into the artifact store
+    and record them.
+    Globs are resolved from the step working directory,
+    and must not match anything outside of the project root.
+    */
+    pub fn collect_artifacts(&mut self, uuid: &Uuid) -> Result<()> {
+        let dest = self.get_artifacts_dir(uuid);
+        let root = self.get_cwd()?;
+        // Pipelines run from the project root
+        let project = env::current_dir().into_diagnostic()?;
+        let project = project.canonicalize().unwrap_or(project);
+        let cwd = root.canonicalize().unwrap_or(root.clone());
+        let Some(artifacts) = self.artifacts.as_mut() else {
+            return Ok(());
+        };
+        let mut files = vec![];
+        for pattern in &artifacts.globs {
+            let pattern = root.join(pattern);
+            for path in glob(&pattern.to_string_lossy())
+                .into_diagnostic()?
+                .flatten()
+            {
+                let path = path.canonicalize().into_diagnostic()?;
+                if !path.starts_with(&project) {
+                    let message = format!(
+                        "The artifact {} is outside of the project root {}",
+                        path.display(),
+                        project.display()
+                    );
+                    return Err(Error::msg(message));
+                }
+                // Keep the path tree relative to the working directory,
+                // or else to the project root
+                let relative = match path.strip_prefix(&cwd) {
+                    Ok(res) => res.to_owned(),
+                    Err(_) => path.strip_prefix(&project).into_diagnostic()?.to_owned(),
+                };
+                files.extend(copy(&path, &dest, &relative)?);
+            }
+        }
+        files.sort();
+        files.dedup();
+        artifacts.files = Some(files);
+        Ok(())
+    }
+    /**
+    Copy the artifacts collected by the step in a previous run
+    into the artifact store of a new run.
+    */
+    pub fn reuse_artifacts(&self, from: &Uuid, to: &Uuid) -> Result<()> {
+        let Some(files) = self.artifacts.as_ref().and_then(|e| e.files.clone()) else {
+            return Ok(());
+        };
+        let source = self.get_artifacts_dir(from);
+        let dest = self.get_artifacts_dir(to);
+        for file in &files {
+            copy(&source.join(file), &dest, Path::new(file))?;
+        }
+        Ok(())
+    }
+}
+
+/**
+Copy a file, or a directory recursively, into the destination directory.
+Return the copied files paths relative to the destination.
+*/
+pub(super) fn copy(path: &Path, dest: &Path, relative: &Path) -> Result<Vec<String>> {
+    let mut files = vec![];
+    if path.is_dir() {
+        for entry in fs::read_dir(path).into_diagnostic()? {
+            let entry = entry.into_diagnostic()?;
+            files.extend(copy(
+                &entry.path(),
+                dest,
+                &relative.join(entry.file_name()),
+            )?);
+        }
+    } else if path.is_file() {
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).into_diagnostic()?;
+        }
+        fs::copy(path, &target).into_diagnostic()?;
+        files.push(relative.display().to_string());
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Artifacts, Pipeline, Step, StepOpts};
+    use miette::Result;
+    use std::fs;
+
+    #[test]
+    fn collect_artifacts() -> Result<()> {
+        let pipeline = Pipeline::default();
+        let mut step = Step {
+            name: "collect".to_owned(),
+            artifacts: Some(Artifacts {
+                globs: vec!["src/step/*.rs".to_owned(), "Cargo.toml".to_owned()],
+                files: None,
+            }),
+            options: Some(StepOpts::default()),
+            ..Default::default()
+        };
+        step.collect_artifacts(&pipeline.uuid)?;
+        let files = step.artifacts.unwrap().files.unwrap();
+        assert!(files.contains(&"Cargo.toml".to_owned()));
+        assert!(files.contains(&"src/step/artifacts.rs".to_owned()));
+
+        let dir = pipeline.get_artifacts_dir();
+        assert!(dir.join("collect/src/step/artifacts.rs").is_file());
+        fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+    #[test]
+    fn collect_artifacts_outside_project() -> Result<()> {
+        let pipeline = Pipeline::default();
+        let mut step = Step {
+            name: "escape".to_owned(),
+            artifacts: Some(Artifacts {
+                globs: vec!["../Cargo.toml".to_owned()],
+                files: None,
+            }),
+            options: Some(StepOpts::default()),
+            ..Default::default()
+        };
+        assert!(step.collect_artifacts(&pipeline.uuid).is_err());
+        assert!(!pipeline.get_artifacts_dir().exists());
+        Ok(())
+    }
+}
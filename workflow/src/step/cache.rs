@@ -0,0 +1,177 @@
+// Structs
+use crate::types::{Pipeline, Step};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+// Filesystem
+use glob::glob;
+use std::fs;
+use std::path::Path;
+// Hash
+use sha2::{Digest, Sha256};
+// Error Handling
+use miette::{IntoDiagnostic, Result};
+
+use super::artifacts::copy;
+
+/**
+Directory that contains the results of successful steps,
+indexed by their cache key.
+*/
+pub static CACHE_DIR: &str = ".pipelight/cache";
+
+/**
+A successful step result stored in the cache.
+*/
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    // The run that produced the entry
+    uuid: Uuid,
+    // Declared outputs, relative to the step working directory
+    files: Vec<String>,
+    // Values written by the commands to $PIPELIGHT_OUTPUT
+    #[serde(default)]
+    outputs: Option<BTreeMap<String, String>>,
+}
+
+impl Step {
+    /**
+    Compute the step cache key.
+    A hash of the matched input files (path and content),
+    the commands as they will run, the environment they will run in
+    and the declared outputs.
+    Commands with unresolved placeholders are never cached.
+    */
+    pub fn get_cache_key(&self, pipeline: &Pipeline) -> Result<Option<String>> {
+        let Some(inputs) = &self.inputs else {
+            return Ok(None);
+        };
+        let root = self.get_cwd()?;
+        let mut hasher = Sha256::new();
+
+        hasher.update(root.to_string_lossy().as_bytes());
+        // Same environment as the commands (see Step::run_commands),
+        // the run identity (pipeline, run uuid, action) left out
+        // so that results are shared between runs.
+        let mut env: BTreeMap<String, String> = pipeline
+            .get_metadata_env(&self.name)
+            .into_iter()
+            .filter(|(key, _)| {
+                ["PIPELIGHT_BRANCH", "PIPELIGHT_TAG", "PIPELIGHT_COMMIT"].contains(&key.as_str())
+            })
+            .collect();
+        env.extend(self.get_secrets());
+        env.extend(self.get_env());
+        for command in &self.commands {
+            let stdin = command.process.io.stdin.clone().unwrap_or_default();
+            let Ok((line, vars)) = self.resolve_command(pipeline, &stdin) else {
+                return Ok(None);
+            };
+            hasher.update([0]);
+            hasher.update(line);
+            env.extend(vars);
+        }
+        for (key, value) in env {
+            hasher.update([0]);
+            hasher.update(format!("{}={}", key, value));
+        }
+        if let Some(artifacts) = &self.artifacts {
+            for pattern in &artifacts.globs {
+                hasher.update([0]);
+                hasher.update(pattern);
+            }
+        }
+
+        let mut files = vec![];
+        for pattern in &inputs.globs {
+            let pattern = root.join(pattern);
+            for path in glob(&pattern.to_string_lossy())
+                .into_diagnostic()?
+                .flatten()
+            {
+                if path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        files.dedup();
+        for path in files {
+            let relative = path.strip_prefix(&root).unwrap_or(&path);
+            hasher.update([0]);
+            hasher.update(relative.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update(fs::read(&path).into_diagnostic()?);
+        }
+        Ok(Some(format!("{:x}", hasher.finalize())))
+    }
+    /**
+    Look for a previous successful run with the same cache key.
+    On cache hit, restore the declared outputs into the step working directory
+    and collect them as this run artifacts,
+    and restore the values the commands wrote to $PIPELIGHT_OUTPUT.
+    Return true on cache hit.
+    */
+    pub fn restore_cache(&mut self, pipeline: &Pipeline) -> Result<bool> {
+        let uuid = &pipeline.uuid;
+        let key = self.get_cache_key(pipeline)?;
+        let Some(inputs) = self.inputs.as_mut() else {
+            return Ok(false);
+        };
+        inputs.key = key.clone();
+        inputs.cached = Some(false);
+        let Some(key) = key else {
+            return Ok(false);
+        };
+
+        let dir = Path::new(CACHE_DIR).join(key);
+        let path = dir.join("entry.json");
+        if !path.is_file() {
+            return Ok(false);
+        }
+        let json = fs::read_to_string(path).into_diagnostic()?;
+        let entry = serde_json::from_str::<Entry>(&json).into_diagnostic()?;
+        let root = self.get_cwd()?;
+        for file in &entry.files {
+            copy(&dir.join("files").join(file), &root, Path::new(file))?;
+        }
+        if self.artifacts.is_some() {
+            self.collect_artifacts(uuid)?;
+        }
+        self.outputs = entry.outputs;
+        if let Some(inputs) = self.inputs.as_mut() {
+            inputs.cached = Some(true);
+        }
+        Ok(true)
+    }
+    /**
+    Store the step declared outputs, and its output values, in the cache
+    under the key computed before the step execution.
+    */
+    pub fn store_cache(&self, uuid: &Uuid) -> Result<()> {
+        let Some(key) = self.inputs.as_ref().and_then(|e| e.key.clone()) else {
+            return Ok(());
+        };
+        let dir = Path::new(CACHE_DIR).join(key);
+        fs::create_dir_all(&dir).into_diagnostic()?;
+
+        let files = self
+            .artifacts
+            .as_ref()
+            .and_then(|e| e.files.clone())
+            .unwrap_or_default();
+        let source = self.get_artifacts_dir(uuid);
+        for file in &files {
+            copy(&source.join(file), &dir.join("files"), Path::new(file))?;
+        }
+        // Written last so that an entry is never partially stored.
+        let entry = Entry {
+            uuid: *uuid,
+            files,
+            outputs: self.outputs.clone(),
+        };
+        let json = serde_json::to_string(&entry).into_diagnostic()?;
+        fs::write(dir.join("entry.json"), json).into_diagnostic()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+// Structs
+use crate::types::{Cancel, Step, StepOrParallel};
+
+impl StepOrParallel {
+    /**
+    Cancel the step, or the steps of the parallel block,
+    once one of the given flags is raised.
+    */
+    pub fn watch(&mut self, flags: &[Cancel]) {
+        match self {
+            StepOrParallel::Step(step) => step.watch(flags),
+            StepOrParallel::Parallel(parallel) => {
+                for step in &mut parallel.steps {
+                    step.watch(flags);
+                }
+            }
+        }
+    }
+    /**
+    Report if the execution the step belongs to has been cancelled
+    (ex: the pipeline abortion).
+    */
+    pub fn is_interrupted(&self) -> bool {
+        match self {
+            StepOrParallel::Step(step) => step.is_interrupted(),
+            StepOrParallel::Parallel(parallel) => parallel.steps.iter().any(|e| e.is_interrupted()),
+        }
+    }
+}
+
+impl Step {
+    /**
+    Cancel the step once one of the given flags is raised.
+    */
+    pub fn watch(&mut self, flags: &[Cancel]) {
+        self.cancelled.watched.extend(flags.iter().cloned());
+    }
+    /**
+    Abort a running step.
+    Only the flag is raised, from any thread:
+    the thread running the step kills its own running commands.
+    */
+    pub fn cancel(&self) {
+        self.cancelled.flag.raise();
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.flag.is_raised() || self.is_interrupted()
+    }
+    fn is_interrupted(&self) -> bool {
+        self.cancelled.watched.iter().any(|e| e.is_raised())
+    }
+    /**
+    Every flag that cancels the step, to be watched by its commands
+    and by the steps of an invoked pipeline.
+    */
+    pub fn get_cancel_flags(&self) -> Vec<Cancel> {
+        let mut flags = vec![self.cancelled.flag.clone()];
+        flags.extend(self.cancelled.watched.iter().cloned());
+        flags
+    }
+    /**
+    Clear the cancellation once the step is done.
+    */
+    pub fn forget_cancel(&self) {
+        self.cancelled.flag.lower();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Cancel, Step, StepOrParallel};
+
+    #[test]
+    fn cancel_is_not_shared() {
+        let step = Step::default();
+        let copy = step.clone();
+        step.cancel();
+        assert!(step.is_cancelled());
+        // Copies have their own flag
+        assert!(!copy.is_cancelled());
+        assert!(!step.clone().is_cancelled());
+        step.forget_cancel();
+        assert!(!step.is_cancelled());
+    }
+    #[test]
+    fn cancel_watched() {
+        let abort = Cancel::new();
+        let mut step = StepOrParallel::Step(Box::default());
+        step.watch(std::slice::from_ref(&abort));
+        assert!(!step.is_interrupted());
+        abort.raise();
+        assert!(step.is_interrupted());
+    }
+}
@@ -0,0 +1,69 @@
+// Structs
+use crate::types::{Fallback, Pipeline, Step, StepOrParallel};
+
+impl Pipeline {
+    /**
+    Nest a copy of the invoked pipelines into the steps that invoke them.
+    The stack holds the pipelines being linked,
+    so that an invocation cycle can't recurse forever.
+    */
+    pub(crate) fn link_pipelines(&mut self, pipelines: &[Pipeline], stack: &mut Vec<String>) {
+        stack.push(self.name.clone());
+        for step in &mut self.steps {
+            step.link_pipelines(pipelines, stack);
+        }
+        if let Some(fallback) = &mut self.fallback {
+            fallback.link_pipelines(pipelines, stack);
+        }
+        stack.pop();
+    }
+}
+impl StepOrParallel {
+    fn link_pipelines(&mut self, pipelines: &[Pipeline], stack: &mut Vec<String>) {
+        match self {
+            StepOrParallel::Step(step) => step.link_pipelines(pipelines, stack),
+            StepOrParallel::Parallel(parallel) => {
+                for step in &mut parallel.steps {
+                    step.link_pipelines(pipelines, stack);
+                }
+                if let Some(fallback) = &mut parallel.fallback {
+                    fallback.link_pipelines(pipelines, stack);
+                }
+            }
+        }
+    }
+}
+impl Fallback {
+    fn link_pipelines(&mut self, pipelines: &[Pipeline], stack: &mut Vec<String>) {
+        for steps in [
+            &mut self.on_started,
+            &mut self.on_failure,
+            &mut self.on_success,
+            &mut self.on_abortion,
+            &mut self.on_finished,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for step in steps {
+                step.link_pipelines(pipelines, stack);
+            }
+        }
+    }
+}
+impl Step {
+    fn link_pipelines(&mut self, pipelines: &[Pipeline], stack: &mut Vec<String>) {
+        if let Some(name) = &self.pipeline {
+            if !stack.contains(name) {
+                if let Some(pipeline) = pipelines.iter().find(|e| &e.name == name) {
+                    let mut child = pipeline.clone();
+                    child.link_pipelines(pipelines, stack);
+                    self.child = Some(Box::new(child));
+                }
+            }
+        }
+        if let Some(fallback) = &mut self.fallback {
+            fallback.link_pipelines(pipelines, stack);
+        }
+    }
+}
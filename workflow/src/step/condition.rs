@@ -0,0 +1,91 @@
+// Structs
+use crate::types::{Pipeline, Secret, Step, StepOrParallel, Trigger};
+use pipelight_exec::Status;
+use pipelight_utils::expression::Expression;
+// Error Handling
+use miette::Result;
+
+impl Pipeline {
+    /**
+    Return the first step with the given name,
+    parallel blocks included.
+    */
+    fn get_step(&self, name: &str) -> Option<&Step> {
+        for step_or_parallel in &self.steps {
+            let steps = match step_or_parallel {
+                StepOrParallel::Step(step) => vec![step.as_ref()],
+                StepOrParallel::Parallel(parallel) => parallel.steps.iter().collect(),
+            };
+            if let Some(step) = steps.into_iter().find(|e| e.name == name) {
+                return Some(step);
+            }
+        }
+        None
+    }
+    fn get_step_status(&self, name: &str) -> Option<Status> {
+        self.get_step(name)?.status.clone()
+    }
+    fn get_step_output(&self, name: &str, key: &str) -> Option<String> {
+        self.get_step(name)?.outputs.as_ref()?.get(key).cloned()
+    }
+    /**
+    Resolve a condition variable:
+    - branch, tag, commit and action from the pipeline triggering event,
+    - env.<NAME> from the process environment variables,
+    - secrets.<NAME> from the loaded secrets,
+    - steps.<name>.status and steps.<name>.outputs.<key> from the previous steps.
+    */
+    pub fn get_variable(&self, path: &str) -> Option<String> {
+        let trigger = self.event.as_ref().map(|e| e.trigger.clone());
+        match path.split_once('.') {
+            Some(("env", name)) => std::env::var(name).ok(),
+            Some(("secrets", name)) => Secret::get_all().get(name).cloned(),
+            Some(("steps", rest)) => {
+                if let Some((name, key)) = rest.split_once(".outputs.") {
+                    return self.get_step_output(name, key);
+                }
+                let (name, field) = rest.rsplit_once('.')?;
+                match field {
+                    "status" => self.get_step_status(name).map(|e| String::from(&e)),
+                    _ => None,
+                }
+            }
+            _ => match (path, trigger?) {
+                ("branch", Trigger::TriggerBranch(e)) => e.branch,
+                ("tag", Trigger::TriggerTag(e)) => e.tag,
+                ("commit", e) => e.get_commit().ok()?,
+                ("action", e) => e.get_action().ok()?.map(|e| String::from(&e)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Step {
+    /**
+    Resolve a variable in the step context:
+    env.<NAME> from the step merged environment (config, pipeline and step),
+    and else from the pipeline execution context.
+    */
+    pub fn get_variable(&self, pipeline: &Pipeline, path: &str) -> Option<String> {
+        if let Some(("env", name)) = path.split_once('.') {
+            if let Some(value) = self.get_env().get(name) {
+                return Some(value.to_owned());
+            }
+        }
+        pipeline.get_variable(path)
+    }
+    /**
+    Evaluate the step condition against the pipeline execution context.
+    A step without condition is always run.
+    */
+    pub fn is_condition_met(&self, pipeline: &Pipeline) -> Result<bool> {
+        match &self.condition {
+            Some(condition) => {
+                let expression = condition.parse::<Expression>()?;
+                Ok(expression.eval(&|path: &str| self.get_variable(pipeline, path)))
+            }
+            None => Ok(true),
+        }
+    }
+}
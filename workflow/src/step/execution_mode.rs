@@ -1,11 +1,11 @@
 // Structs
-use crate::types::{Mode, Step, StepOrParallel};
+use crate::types::{Mode, Parallel, Step, StepOrParallel};
 
 impl StepOrParallel {
     pub fn get_mode(&self) -> Option<Mode> {
         match self {
             StepOrParallel::Step(res) => res.get_mode(),
-            StepOrParallel::Parallel(_) => None,
+            StepOrParallel::Parallel(res) => res.get_mode(),
         }
     }
 }
@@ -18,3 +18,8 @@ impl Step {
         }
     }
 }
+impl Parallel {
+    pub fn get_mode(&self) -> Option<Mode> {
+        self.options.as_ref().and_then(|e| e.mode.clone())
+    }
+}
@@ -1,12 +1,30 @@
 // Structs
 use crate::types::{Fallback, Parallel, Step, StepOrParallel};
 use pipelight_exec::Process;
+// Filesystem
+use std::env;
+use std::path::PathBuf;
 // Error Handling
-use miette::Result;
+use miette::{IntoDiagnostic, Result};
 
 impl Step {
+    /**
+    Return the directory the step commands run into.
+    */
+    pub fn get_cwd(&self) -> Result<PathBuf> {
+        match self.options.as_ref().and_then(|e| e.cwd.clone()) {
+            Some(cwd) => Ok(PathBuf::from(cwd)),
+            None => env::current_dir().into_diagnostic(),
+        }
+    }
     pub fn get_procs(&self) -> Result<Vec<Process>> {
         let mut procs: Vec<Process> = vec![];
+        // Previous attempts processes
+        for attempt in self.attempts.clone().unwrap_or_default() {
+            for command in attempt.commands {
+                procs.push(command.process);
+            }
+        }
         for command in self.commands.clone() {
             procs.push(command.process);
         }
@@ -23,6 +41,25 @@ impl Parallel {
     }
 }
 impl StepOrParallel {
+    /**
+    Return the step name.
+    Parallel blocks are unnamed unless expanded from a matrix step.
+    */
+    pub fn get_name(&self) -> Option<String> {
+        match self {
+            StepOrParallel::Step(res) => Some(res.name.clone()),
+            StepOrParallel::Parallel(res) => res.name.clone(),
+        }
+    }
+    /**
+    Return the names of the steps that must be done before this one starts.
+    */
+    pub fn get_needs(&self) -> Vec<String> {
+        match self {
+            StepOrParallel::Step(res) => res.needs.clone().unwrap_or_default(),
+            StepOrParallel::Parallel(res) => res.needs.clone().unwrap_or_default(),
+        }
+    }
     pub fn get_procs(&self) -> Result<Vec<Process>> {
         let mut procs: Vec<Process> = vec![];
         match self {
@@ -44,6 +81,7 @@ impl Fallback {
             self.on_failure.clone(),
             self.on_success.clone(),
             self.on_abortion.clone(),
+            self.on_finished.clone(),
         ];
         for vec_step_or_parallel in fallbacks {
             if let Some(vec_step_or_parallel) = vec_step_or_parallel {
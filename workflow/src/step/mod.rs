@@ -1,2 +1,11 @@
+mod artifacts;
+mod cache;
+mod cancel;
+mod compose;
+mod condition;
 mod execution_mode;
 mod getters;
+mod outputs;
+mod resolve;
+mod retry;
+mod secrets;
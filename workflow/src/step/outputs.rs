@@ -0,0 +1,101 @@
+// Structs
+use crate::types::{Pipeline, Step};
+use std::collections::BTreeMap;
+// Filesystem
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+// Error Handling
+use miette::{IntoDiagnostic, Result};
+
+/**
+Directory that contains the files the steps write their outputs to.
+*/
+pub static OUTPUTS_DIR: &str = ".pipelight/outputs";
+
+fn get_run_outputs_dir(uuid: &Uuid) -> PathBuf {
+    Path::new(OUTPUTS_DIR).join(uuid.to_string())
+}
+
+/**
+Parse the `key=value` lines of an output file.
+Lines without a key are ignored, and a later value overrides an earlier one.
+*/
+fn parse_outputs(content: &str) -> BTreeMap<String, String> {
+    let mut outputs = BTreeMap::new();
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            if !key.is_empty() {
+                outputs.insert(key.to_owned(), value.to_owned());
+            }
+        }
+    }
+    outputs
+}
+
+impl Pipeline {
+    /**
+    Return the directory that contains the run output files:
+    .pipelight/outputs/<pipeline-uuid>
+    */
+    pub fn get_outputs_dir(&self) -> PathBuf {
+        get_run_outputs_dir(&self.uuid)
+    }
+}
+impl Step {
+    /**
+    Return the absolute path of the file the step commands write their outputs to:
+    .pipelight/outputs/<pipeline-uuid>/<step>
+    It is exported to the commands as $PIPELIGHT_OUTPUT.
+    */
+    pub fn get_output_path(&self, uuid: &Uuid) -> Result<PathBuf> {
+        let root = std::env::current_dir().into_diagnostic()?;
+        Ok(root
+            .join(get_run_outputs_dir(uuid))
+            .join(self.name.replace(std::path::MAIN_SEPARATOR, "_")))
+    }
+    /**
+    Create an empty output file for the next commands execution.
+    */
+    pub fn prepare_outputs(&self, uuid: &Uuid) -> Result<PathBuf> {
+        let path = self.get_output_path(uuid)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).into_diagnostic()?;
+        }
+        fs::write(&path, "").into_diagnostic()?;
+        Ok(path)
+    }
+    /**
+    Read the values written by the commands into the step outputs,
+    then remove the output file.
+    */
+    pub fn collect_outputs(&mut self, uuid: &Uuid) -> Result<()> {
+        let path = self.get_output_path(uuid)?;
+        if !path.is_file() {
+            return Ok(());
+        }
+        let content = fs::read_to_string(&path).into_diagnostic()?;
+        let outputs = parse_outputs(&content);
+        if !outputs.is_empty() {
+            self.outputs = Some(outputs);
+        }
+        fs::remove_file(&path).into_diagnostic()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_outputs;
+
+    #[test]
+    fn parse() {
+        let outputs =
+            parse_outputs("tag=v1.2.0\n\nimage = app:latest\nbroken\ntag=v1.2.1\nurl=a=b\n");
+        assert_eq!(outputs.get("tag"), Some(&"v1.2.1".to_owned()));
+        assert_eq!(outputs.get("image"), Some(&" app:latest".to_owned()));
+        assert_eq!(outputs.get("url"), Some(&"a=b".to_owned()));
+        assert_eq!(outputs.len(), 3);
+    }
+}
@@ -0,0 +1,194 @@
+// Structs
+use crate::types::{Pipeline, Secret, Step};
+use pipelight_utils::expression::interpolate_at;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+// Error Handling
+use miette::{Error, Result};
+
+/**
+The shell quoting a placeholder stands in.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+}
+
+/**
+Follow the shell quoting of a command line up to its end.
+*/
+fn get_quote(line: &str) -> Quote {
+    let mut quote = Quote::None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Quote::Single, '\'') => quote = Quote::None,
+            (Quote::Single, _) => {}
+            (_, '\\') => {
+                chars.next();
+            }
+            (Quote::None, '\'') => quote = Quote::Single,
+            (Quote::None, '"') => quote = Quote::Double,
+            (Quote::Double, '"') => quote = Quote::None,
+            _ => {}
+        }
+    }
+    quote
+}
+
+/**
+A reference to an environment variable, quoted for where it stands in the command line,
+so that the shell expands it to a single word and never reads it as code.
+*/
+fn get_reference(name: &str, quote: Quote) -> String {
+    match quote {
+        Quote::None => format!("\"${}\"", name),
+        Quote::Double => format!("${{{}}}", name),
+        Quote::Single => format!("'\"${}\"'", name),
+    }
+}
+
+/**
+The environment variable a step output is exported to:
+PIPELIGHT_STEPS_<STEP>_OUTPUTS_<KEY>, uppercased,
+with every character that can't be in a variable name replaced by `_`.
+*/
+pub fn get_output_variable(step: &str, key: &str) -> String {
+    format!("PIPELIGHT_STEPS_{}_OUTPUTS_{}", step, key)
+        .chars()
+        .map(|e| match e.is_ascii_alphanumeric() {
+            true => e.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+impl Step {
+    /**
+    Resolve the `${{ ... }}` placeholders of a step command line
+    against the pipeline execution context,
+    and return the command line with the variables to export to it.
+
+    Secret values and step outputs are never written into the command line,
+    that ends up in the process arguments and is read by the shell as code:
+    secrets are exported to the commands under their own name (see get_secrets),
+    outputs under PIPELIGHT_STEPS_<STEP>_OUTPUTS_<KEY>,
+    and the placeholder is replaced by a reference to the variable.
+
+    A placeholder that can't be resolved is an error,
+    the shell would fail on it anyway.
+    */
+    pub fn resolve_command(
+        &self,
+        pipeline: &Pipeline,
+        line: &str,
+    ) -> Result<(String, BTreeMap<String, String>)> {
+        let env: RefCell<BTreeMap<String, String>> = RefCell::new(BTreeMap::new());
+        let unresolved: RefCell<Vec<String>> = RefCell::new(vec![]);
+        let lookup = |path: &str, before: &str| {
+            let value = match path.split_once('.') {
+                Some(("secrets", name)) => Secret::get_all()
+                    .contains_key(name)
+                    .then(|| get_reference(name, get_quote(before))),
+                Some(("steps", rest)) if rest.contains(".outputs.") => {
+                    let (step, key) = rest.split_once(".outputs.").unwrap_or_default();
+                    pipeline.get_variable(path).map(|value| {
+                        let name = get_output_variable(step, key);
+                        let reference = get_reference(&name, get_quote(before));
+                        env.borrow_mut().insert(name, value);
+                        reference
+                    })
+                }
+                _ => self.get_variable(pipeline, path),
+            };
+            if value.is_none() {
+                unresolved.borrow_mut().push(path.to_owned());
+            }
+            value
+        };
+        let line = interpolate_at(line, &lookup);
+        let unresolved = unresolved.into_inner();
+        if !unresolved.is_empty() {
+            let message = format!(
+                "Couldn't resolve {} in the command: {}",
+                unresolved
+                    .iter()
+                    .map(|e| format!("${{{{ {} }}}}", e))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                line
+            );
+            return Err(Error::msg(message));
+        }
+        Ok((line, env.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::get_output_variable;
+    use crate::globals::SECRETS;
+    use crate::types::{Pipeline, Step, StepOrParallel};
+    use miette::Result;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn resolve_secrets() -> Result<()> {
+        SECRETS
+            .lock()
+            .unwrap()
+            .insert("TEST_RESOLVE".to_owned(), "s3cr3t".to_owned());
+        let step = Step::default();
+        let pipeline = Pipeline::default();
+        let cases = [
+            ("echo ${{ secrets.TEST_RESOLVE }}", "echo \"$TEST_RESOLVE\""),
+            (
+                "echo \"token: ${{ secrets.TEST_RESOLVE }}\"",
+                "echo \"token: ${TEST_RESOLVE}\"",
+            ),
+            (
+                "echo 'token: ${{ secrets.TEST_RESOLVE }}'",
+                "echo 'token: '\"$TEST_RESOLVE\"''",
+            ),
+            (
+                "echo \\\" ${{ secrets.TEST_RESOLVE }}",
+                "echo \\\" \"$TEST_RESOLVE\"",
+            ),
+        ];
+        for (line, res) in cases {
+            assert_eq!(step.resolve_command(&pipeline, line)?.0, res);
+        }
+        assert!(step
+            .resolve_command(&pipeline, "echo ${{ secrets.TEST_UNKNOWN }}")
+            .is_err());
+        Ok(())
+    }
+    #[test]
+    fn resolve_outputs() -> Result<()> {
+        let value = "$(touch pwned); echo \"'".to_owned();
+        let pipeline = Pipeline {
+            steps: vec![StepOrParallel::Step(Box::new(Step {
+                name: "get-version".to_owned(),
+                outputs: Some(BTreeMap::from([("tag".to_owned(), value.clone())])),
+                ..Default::default()
+            }))],
+            ..Default::default()
+        };
+        let name = get_output_variable("get-version", "tag");
+        assert_eq!(name, "PIPELIGHT_STEPS_GET_VERSION_OUTPUTS_TAG");
+
+        let step = Step::default();
+        let (line, env) =
+            step.resolve_command(&pipeline, "test ${{ steps.get-version.outputs.tag }} = x")?;
+        assert_eq!(line, format!("test \"${}\" = x", name));
+        assert_eq!(env.get(&name), Some(&value));
+
+        let err = step
+            .resolve_command(&pipeline, "echo ${{ steps.get-version.outputs.none }}")
+            .unwrap_err();
+        assert!(err.to_string().contains("steps.get-version.outputs.none"));
+        Ok(())
+    }
+}
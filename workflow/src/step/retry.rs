@@ -0,0 +1,67 @@
+// Structs
+use crate::types::{Backoff, Retry, Step};
+// Durations
+use pipelight_exec::dates::convert::human_duration_to_std_duration;
+use std::time;
+// Error Handling
+use miette::Result;
+
+impl Step {
+    pub fn get_retry(&self) -> Option<Retry> {
+        if let Some(options) = &self.options {
+            options.retry.clone()
+        } else {
+            None
+        }
+    }
+    /**
+    Return the number of the current attempt, starting at 1.
+    */
+    pub fn get_attempt(&self) -> u32 {
+        self.attempts.as_ref().map(|e| e.len()).unwrap_or_default() as u32 + 1
+    }
+}
+
+impl Retry {
+    /**
+    Return the time to wait after the nth failed attempt (starting at 1).
+    - constant: delay,
+    - linear: delay * n,
+    - exponential: delay * 2^(n-1).
+    */
+    pub fn get_delay(&self, attempt: u32) -> Result<time::Duration> {
+        let delay = match &self.delay {
+            Some(delay) => human_duration_to_std_duration(delay)?,
+            None => time::Duration::ZERO,
+        };
+        let factor = match self.backoff {
+            Backoff::Constant => 1,
+            Backoff::Linear => attempt,
+            Backoff::Exponential => 2_u32.saturating_pow(attempt.saturating_sub(1)),
+        };
+        Ok(delay.saturating_mul(factor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{Backoff, Retry};
+    use miette::Result;
+    use std::time;
+
+    #[test]
+    fn backoff_delays() -> Result<()> {
+        let mut retry = Retry {
+            attempts: 4,
+            delay: Some("5s".to_owned()),
+            backoff: Backoff::Constant,
+        };
+        assert_eq!(retry.get_delay(3)?, time::Duration::from_secs(5));
+        retry.backoff = Backoff::Linear;
+        assert_eq!(retry.get_delay(3)?, time::Duration::from_secs(15));
+        retry.backoff = Backoff::Exponential;
+        assert_eq!(retry.get_delay(1)?, time::Duration::from_secs(5));
+        assert_eq!(retry.get_delay(3)?, time::Duration::from_secs(20));
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+// Structs
+use crate::types::{Secret, Step};
+use pipelight_utils::expression::interpolate;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+impl Step {
+    /**
+    Return the loaded secrets the step commands reference
+    with `${{ secrets.<NAME> }}`, by name.
+    Only those are exported to the step commands environment,
+    while every loaded secret is still masked.
+    */
+    pub fn get_secrets(&self) -> BTreeMap<String, String> {
+        let names: RefCell<Vec<String>> = RefCell::new(vec![]);
+        let lookup = |path: &str| {
+            if let Some(name) = path.strip_prefix("secrets.") {
+                names.borrow_mut().push(name.to_owned());
+            }
+            None
+        };
+        for command in &self.commands {
+            if let Some(stdin) = &command.process.io.stdin {
+                interpolate(stdin, &lookup);
+            }
+        }
+        let names = names.into_inner();
+        Secret::get_all()
+            .into_iter()
+            .filter(|(name, _)| names.contains(name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::globals::SECRETS;
+    use crate::types::{Command, Step};
+
+    #[test]
+    fn get_secrets() {
+        SECRETS.lock().unwrap().extend([
+            ("TEST_USED".to_owned(), "used".to_owned()),
+            ("TEST_UNUSED".to_owned(), "unused".to_owned()),
+        ]);
+        let step = Step {
+            commands: vec![
+                Command::new("echo ${{ secrets.TEST_USED }}"),
+                Command::new("echo ${{ secrets.TEST_UNKNOWN }}"),
+            ],
+            ..Default::default()
+        };
+        let secrets = step.get_secrets();
+        assert_eq!(secrets.get("TEST_USED"), Some(&"used".to_owned()));
+        assert!(!secrets.contains_key("TEST_UNUSED"));
+        assert!(!secrets.contains_key("TEST_UNKNOWN"));
+    }
+}
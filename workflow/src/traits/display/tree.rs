@@ -81,6 +81,8 @@ impl Node {
                     Some(Status::Succeeded) => println!("{}", &value.blue()),
                     Some(Status::Failed) => println!("{}", &value.red()),
                     Some(Status::Aborted) => println!("{}", &value.yellow()),
+                    Some(Status::TimedOut) => println!("{}", &value.magenta()),
+                    Some(Status::Skipped) => println!("{}", &value.dimmed()),
                     None => println!("{}", &value.white()),
                 }
             }
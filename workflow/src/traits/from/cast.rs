@@ -1,15 +1,18 @@
 use crate::pipeline::Filters;
 use crate::types::{
-    Command, Config, ConfigOpts, Fallback, Mode, Parallel, Pipeline, PipelineOpts, Step, StepOpts,
+    Artifacts, Backoff, Command, Concurrency, Config, ConfigOpts, Fallback, Inputs, Limits, Mode,
+    Parallel, ParallelOpts, Pipeline, PipelineOpts, Retry, Sandbox, Secret, Step, StepOpts,
     StepOrParallel,
 };
 use crate::types::{Trigger, TriggerBranch, TriggerTag};
 use log::LevelFilter;
-use pipelight_exec::Process;
+use pipelight_exec::dates::convert::human_duration_to_std_duration;
+use pipelight_exec::{human_size_to_bytes, Process};
 
 use convert_case::{Case, Casing};
 
 use pipelight_utils::git::Flag;
+use std::collections::BTreeMap;
 use std::convert::From;
 use std::process::exit;
 use uuid::Uuid;
@@ -27,6 +30,8 @@ impl From<&cast::ConfigOpts> for ConfigOpts {
         if let Some(attach) = e.attach {
             options.attach = Some(attach);
         }
+        options.env = e.env.clone();
+        options.max_jobs = e.max_jobs;
         options
     }
 }
@@ -46,12 +51,38 @@ impl From<&cast::Config> for Config {
                 .map(Pipeline::from)
                 .collect();
             binding_pipelines = Filters::dedup(binding_pipelines).unwrap();
+            // Pipelines inherit the config environment
+            if let Some(env) = options.as_ref().and_then(|e: &ConfigOpts| e.env.clone()) {
+                for pipeline in &mut binding_pipelines {
+                    pipeline.inherit_env(&env);
+                }
+            }
+            // Nest the invoked pipelines into the steps
+            let all = binding_pipelines.clone();
+            for pipeline in &mut binding_pipelines {
+                pipeline.link_pipelines(&all, &mut vec![]);
+            }
             pipelines = Some(binding_pipelines);
         }
+        // Secrets
+        let secrets = e.secrets.as_ref().map(|e| {
+            e.iter()
+                .map(|(name, secret)| (name.clone(), Secret::from(secret)))
+                .collect()
+        });
         Config {
             pipelines,
             options,
-            ..Config::default()
+            secrets,
+        }
+    }
+}
+
+impl From<&cast::Secret> for Secret {
+    fn from(e: &cast::Secret) -> Self {
+        Secret {
+            env: e.env.clone(),
+            file: e.file.clone(),
         }
     }
 }
@@ -66,6 +97,8 @@ impl From<&cast::PipelineOpts> for PipelineOpts {
         if let Some(attach) = e.attach {
             options.attach = Some(attach);
         }
+        options.env = e.env.clone();
+        options.concurrency = e.concurrency.as_ref().map(Concurrency::from);
         options
     }
 }
@@ -105,23 +138,32 @@ impl From<&cast::Pipeline> for Pipeline {
                     .collect::<Vec<Trigger>>(),
             )
         };
-        Pipeline {
+        let mut pipeline = Pipeline {
             uuid: Uuid::new_v4(),
             name: e.name.to_owned(),
             steps: steps.to_owned(),
+            timeout: e.timeout.clone(),
             triggers,
             fallback,
             options,
             ..Pipeline::default()
-        }
+        };
+        // Steps inherit the pipeline environment
+        pipeline.inherit_env(&BTreeMap::new());
+        pipeline
     }
 }
 
 impl From<&cast::StepOrParallel> for StepOrParallel {
     fn from(e: &cast::StepOrParallel) -> Self {
         match e {
-            cast::StepOrParallel::Step(res) => StepOrParallel::Step(Step::from(res)),
-            cast::StepOrParallel::Parallel(res) => StepOrParallel::Parallel(Parallel::from(res)),
+            cast::StepOrParallel::Step(res) => match res.matrix {
+                Some(_) => StepOrParallel::Parallel(Box::new(Parallel::from_matrix_step(res))),
+                None => StepOrParallel::Step(Box::new(Step::from(res.as_ref()))),
+            },
+            cast::StepOrParallel::Parallel(res) => {
+                StepOrParallel::Parallel(Box::new(Parallel::from(res.as_ref())))
+            }
         }
     }
 }
@@ -132,10 +174,40 @@ impl From<&cast::StepOpts> for StepOpts {
         if let Some(mode) = &e.mode {
             options.mode = Some(Mode::from(mode));
         }
+        if let Some(retry) = &e.retry {
+            options.retry = Some(Retry::from(retry));
+        }
+        options.env = e.env.clone();
+        options.cwd = e.cwd.clone();
+        options.sandbox = e.sandbox.as_ref().map(|e| Sandbox {
+            network: e.network.unwrap_or_default(),
+            ..Sandbox::default()
+        });
+        // Sizes and durations are checked beforehand (see cast strict rules).
+        options.limits = e.limits.as_ref().map(|e| Limits {
+            memory: e.memory.as_ref().and_then(|e| human_size_to_bytes(e).ok()),
+            cpu_time: e
+                .cpu_time
+                .as_ref()
+                .and_then(|e| human_duration_to_std_duration(e).ok())
+                .map(|e| e.as_secs_f64().ceil() as u64),
+            nofile: e.nofile,
+            nproc: e.nproc,
+        });
         options
     }
 }
 
+impl From<&cast::Retry> for Retry {
+    fn from(e: &cast::Retry) -> Self {
+        Retry {
+            attempts: e.attempts,
+            delay: e.delay.clone(),
+            backoff: e.backoff.as_ref().map(Backoff::from).unwrap_or_default(),
+        }
+    }
+}
+
 impl From<&cast::Step> for Step {
     fn from(e: &cast::Step) -> Self {
         let commands = e
@@ -159,6 +231,18 @@ impl From<&cast::Step> for Step {
         Step {
             name: e.clone().name,
             commands,
+            needs: e.needs.clone(),
+            timeout: e.timeout.clone(),
+            condition: e.condition.clone(),
+            artifacts: e
+                .artifacts
+                .clone()
+                .map(|globs| Artifacts { globs, files: None }),
+            inputs: e.inputs.clone().map(|globs| Inputs {
+                globs,
+                ..Inputs::default()
+            }),
+            pipeline: e.pipeline.clone(),
             fallback,
             options,
             ..Step::default()
@@ -172,13 +256,29 @@ impl From<&cast::Parallel> for Parallel {
         if e.fallback.is_some() {
             fallback = Some(Fallback::from(e.fallback.as_ref().unwrap()));
         }
+        // Options
+        let mut options = None;
+        if e.max_parallel.is_some() || e.fail_fast.is_some() || e.mode.is_some() {
+            options = Some(ParallelOpts {
+                max_parallel: e.max_parallel,
+                fail_fast: e.fail_fast,
+                mode: e.mode.as_ref().map(Mode::from),
+            });
+        }
         let mut res = Parallel {
             fallback,
             steps: vec![],
+            timeout: e.timeout.clone(),
+            options,
             ..Parallel::default()
         };
-        for step in &e.parallel {
-            res.steps.push(Step::from(step));
+        match e.matrix {
+            Some(_) => res.steps = Parallel::expand_matrix(e),
+            None => {
+                for step in &e.parallel {
+                    res.steps.push(Step::from(step));
+                }
+            }
         }
         res
     }
@@ -239,11 +339,24 @@ impl From<&cast::Fallback> for Fallback {
                     .collect::<Vec<StepOrParallel>>(),
             );
         }
+        // Convert post-run steps
+        let mut on_finished = None;
+        if e.on_finished.is_some() {
+            let binding = e.on_finished.clone().unwrap();
+            on_finished = Some(
+                binding
+                    .iter()
+                    .map(StepOrParallel::from)
+                    .collect::<Vec<StepOrParallel>>(),
+            );
+        }
         Fallback {
             on_started,
             on_failure,
             on_success,
             on_abortion,
+            on_finished,
+            on_finished_status: None,
         }
     }
 }
@@ -333,6 +446,37 @@ impl From<&String> for Mode {
         }
     }
 }
+impl From<&String> for Concurrency {
+    fn from(concurrency: &String) -> Concurrency {
+        let cased: &str = &concurrency.to_case(Case::Snake);
+        match cased {
+            "skip" => Concurrency::Skip,
+            "queue" => Concurrency::Queue,
+            "cancel_previous" => Concurrency::CancelPrevious,
+            "allow" => Concurrency::Allow,
+            _ => {
+                let message = format!("The concurrency policy {} is not known", cased);
+                error!("{}", message);
+                exit(1);
+            }
+        }
+    }
+}
+impl From<&String> for Backoff {
+    fn from(backoff: &String) -> Backoff {
+        let cased: &str = &backoff.to_case(Case::Snake);
+        match cased {
+            "constant" => Backoff::Constant,
+            "linear" => Backoff::Linear,
+            "exponential" => Backoff::Exponential,
+            _ => {
+                let message = format!("The retry backoff {} is not known", cased);
+                error!("{}", message);
+                exit(1);
+            }
+        }
+    }
+}
 impl From<&Mode> for String {
     fn from(mode: &Mode) -> String {
         match mode {
@@ -0,0 +1,156 @@
+/**
+Expand matrix steps and matrix parallel blocks
+into one step per combination of the matrix values.
+*/
+// Structs
+use crate::types::{Parallel, ParallelOpts, Step};
+use std::collections::BTreeMap;
+// Interpolation
+use pipelight_utils::expression::interpolate;
+
+/**
+Return the cartesian product of the matrix values,
+as a list of variable name to value maps.
+*/
+pub fn get_combinations(matrix: &cast::Matrix) -> Vec<BTreeMap<String, String>> {
+    let mut combinations: Vec<BTreeMap<String, String>> = vec![BTreeMap::new()];
+    for (key, values) in matrix {
+        combinations = combinations
+            .iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.insert(key.to_owned(), value.to_owned());
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
+/**
+Generate the step of a matrix combination.
+`${{ matrix.<name> }}` placeholders are replaced in the step name, commands and condition.
+If the name has no placeholder, the combination values are appended to it.
+*/
+fn expand_step(e: &cast::Step, combination: &BTreeMap<String, String>) -> cast::Step {
+    let lookup = |path: &str| {
+        path.strip_prefix("matrix.")
+            .and_then(|key| combination.get(key).cloned())
+    };
+    let mut name = interpolate(&e.name, &lookup);
+    if name == e.name {
+        let values: Vec<String> = combination.values().cloned().collect();
+        name = format!("{} ({})", e.name, values.join(", "));
+    }
+    cast::Step {
+        name,
+        commands: e.commands.iter().map(|e| interpolate(e, &lookup)).collect(),
+        condition: e.condition.as_ref().map(|e| interpolate(e, &lookup)),
+        artifacts: e
+            .artifacts
+            .as_ref()
+            .map(|e| e.iter().map(|e| interpolate(e, &lookup)).collect()),
+        inputs: e
+            .inputs
+            .as_ref()
+            .map(|e| e.iter().map(|e| interpolate(e, &lookup)).collect()),
+        matrix: None,
+        max_parallel: None,
+        fail_fast: None,
+        ..e.clone()
+    }
+}
+
+/**
+Remove the `${{ matrix.<name> }}` placeholders from a name,
+(ex: "test ${{ matrix.toolchain }}" becomes "test").
+*/
+fn strip_matrix(name: &str) -> String {
+    let lookup = |path: &str| path.strip_prefix("matrix.").map(|_| "".to_owned());
+    interpolate(name, &lookup)
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+impl Parallel {
+    /**
+    Expand a matrix step into a parallel block named after the step,
+    without its matrix placeholders.
+    */
+    pub fn from_matrix_step(e: &cast::Step) -> Parallel {
+        let steps = get_combinations(e.matrix.as_ref().unwrap())
+            .iter()
+            .map(|combination| Step::from(&expand_step(e, combination)))
+            .map(|step| Step {
+                needs: None,
+                ..step
+            })
+            .collect();
+        Parallel {
+            name: Some(strip_matrix(&e.name)),
+            needs: e.needs.clone(),
+            steps,
+            options: Some(ParallelOpts {
+                max_parallel: e.max_parallel,
+                fail_fast: e.fail_fast,
+                ..ParallelOpts::default()
+            }),
+            ..Parallel::default()
+        }
+    }
+    /**
+    Expand every step of a matrix parallel block.
+    */
+    pub fn expand_matrix(e: &cast::Parallel) -> Vec<Step> {
+        let mut steps = vec![];
+        for combination in get_combinations(e.matrix.as_ref().unwrap()) {
+            for step in &e.parallel {
+                steps.push(Step::from(&expand_step(step, &combination)));
+            }
+        }
+        steps
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::get_combinations;
+    use crate::types::{Parallel, StepOrParallel};
+
+    #[test]
+    fn combinations() {
+        let matrix = cast::Matrix::from([
+            (
+                "toolchain".to_owned(),
+                vec!["stable".to_owned(), "nightly".to_owned()],
+            ),
+            ("features".to_owned(), vec!["a".to_owned(), "b".to_owned()]),
+        ]);
+        assert_eq!(get_combinations(&matrix).len(), 4);
+    }
+    #[test]
+    fn expand_step() {
+        let json = r#"
+          {
+            "name": "test ${{ matrix.toolchain }}",
+            "commands": ["cargo +${{ matrix.toolchain }} test"],
+            "matrix": { "toolchain": ["stable", "nightly"] },
+            "max_parallel": 1
+          }
+        "#;
+        let step = serde_json::from_str::<cast::StepOrParallel>(json).unwrap();
+        let StepOrParallel::Parallel(parallel) = StepOrParallel::from(&step) else {
+            panic!("expected a parallel block");
+        };
+        let Parallel { name, steps, .. } = *parallel;
+        assert_eq!(name, Some("test".to_owned()));
+        assert_eq!(steps[0].name, "test stable");
+        assert_eq!(
+            steps[1].commands[0].process.io.stdin,
+            Some("cargo +nightly test".to_owned())
+        );
+    }
+}
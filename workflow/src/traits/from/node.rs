@@ -4,7 +4,9 @@ Every pipeline subcomponent such as step and commands are converted to
 the node pretty printable type.
 */
 // Struct
-use crate::types::{Command, Event, Node, Parallel, Pipeline, Step, StepOrParallel, Trigger};
+use crate::types::{
+    Attempt, Command, Event, Fallback, Node, Parallel, Pipeline, Step, StepOrParallel, Trigger,
+};
 use log::LevelFilter;
 use pipelight_exec::{Statuable, Status};
 use pipelight_utils::git::Flag;
@@ -86,6 +88,14 @@ impl From<&Pipeline> for Node {
 
         let name = format!("pipeline: {}", e.name.clone());
         head.push_str(&name);
+
+        // Repeated or resumed run
+        if let Some(origin) = &e.origin {
+            match e.has_reused_steps() {
+                true => head.push_str(&format!(" (resumes {})", origin)),
+                false => head.push_str(&format!(" (rerun of {})", origin)),
+            }
+        }
         let mut children: Vec<Node> = e.steps.iter().map(Node::from).collect();
 
         // Duration
@@ -95,38 +105,8 @@ impl From<&Pipeline> for Node {
             duration = Some(String::from(e.duration.as_ref().unwrap()));
         }
         // Fallback
-        if e.fallback.is_some() {
-            if e.fallback.clone().unwrap().on_failure.is_some() {
-                let on_failure = e.fallback.clone().unwrap().on_failure.unwrap();
-
-                let on_failure_children = on_failure.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_failure_children),
-                    value: Some("on_failure".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
-            if e.fallback.clone().unwrap().on_success.is_some() {
-                let on_success = e.fallback.clone().unwrap().on_success.unwrap();
-                let on_success_children = on_success.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_success_children),
-                    value: Some("on_success".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
-            if e.fallback.clone().unwrap().on_abortion.is_some() {
-                let on_abortion = e.fallback.clone().unwrap().on_abortion.unwrap();
-                let on_abortion_children = on_abortion.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_abortion_children),
-                    value: Some("on_abortion".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
+        if let Some(fallback) = &e.fallback {
+            children.extend(Vec::<Node>::from(fallback));
         }
         Node {
             value: Some(head),
@@ -137,11 +117,39 @@ impl From<&Pipeline> for Node {
         }
     }
 }
+/**
+One node per defined fallback, in execution order.
+*/
+impl From<&Fallback> for Vec<Node> {
+    fn from(e: &Fallback) -> Self {
+        let mut nodes: Vec<Node> = vec![];
+        for (name, steps) in [
+            ("on_started", &e.on_started),
+            ("on_failure", &e.on_failure),
+            ("on_success", &e.on_success),
+            ("on_abortion", &e.on_abortion),
+            ("on_finished", &e.on_finished),
+        ] {
+            if let Some(steps) = steps {
+                let mut node = Node {
+                    children: Some(steps.iter().map(Node::from).collect()),
+                    value: Some(name.to_owned()),
+                    ..Node::default()
+                };
+                if name == "on_finished" {
+                    node.status = e.on_finished_status.clone();
+                }
+                nodes.push(node);
+            }
+        }
+        nodes
+    }
+}
 impl From<&StepOrParallel> for Node {
     fn from(e: &StepOrParallel) -> Self {
         match e {
-            StepOrParallel::Step(res) => Node::from(res),
-            StepOrParallel::Parallel(res) => Node::from(res),
+            StepOrParallel::Step(res) => Node::from(res.as_ref()),
+            StepOrParallel::Parallel(res) => Node::from(res.as_ref()),
         }
     }
 }
@@ -156,41 +164,15 @@ impl From<&Parallel> for Node {
         }
 
         // Fallback
-        if e.fallback.is_some() {
-            if e.fallback.clone().unwrap().on_failure.is_some() {
-                let on_failure = e.fallback.clone().unwrap().on_failure.unwrap();
-
-                let on_failure_children = on_failure.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_failure_children),
-                    value: Some("on_failure".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
-            if e.fallback.clone().unwrap().on_success.is_some() {
-                let on_success = e.fallback.clone().unwrap().on_success.unwrap();
-                let on_success_children = on_success.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_success_children),
-                    value: Some("on_success".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
-            if e.fallback.clone().unwrap().on_abortion.is_some() {
-                let on_abortion = e.fallback.clone().unwrap().on_abortion.unwrap();
-                let on_abortion_children = on_abortion.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_abortion_children),
-                    value: Some("on_abortion".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
+        if let Some(fallback) = &e.fallback {
+            children.extend(Vec::<Node>::from(fallback));
+        }
+        let mut head = "parallel".to_owned();
+        if let Some(name) = &e.name {
+            head.push_str(&format!(": {}", name));
         }
         Node {
-            value: Some("parallel".to_owned()),
+            value: Some(head),
             status: e.status.clone(),
             duration,
             children: Some(children),
@@ -200,8 +182,48 @@ impl From<&Parallel> for Node {
 }
 impl From<&Step> for Node {
     fn from(e: &Step) -> Self {
-        let head = format!("step: {}", e.name.clone());
-        let mut children: Vec<Node> = e.commands.iter().map(Node::from).collect();
+        let mut head = format!("step: {}", e.name.clone());
+
+        // Skipped step: show the unmet condition
+        if e.status == Some(Status::Skipped) {
+            if let Some(condition) = &e.condition {
+                head.push_str(&format!(" (if {})", condition));
+            }
+        }
+
+        // Cached step: the result comes from a previous run
+        if e.inputs.as_ref().and_then(|e| e.cached) == Some(true) {
+            head.push_str(" (cached)");
+        }
+        // Reused step: the result comes from the resumed run
+        if e.reused == Some(true) {
+            head.push_str(" (reused)");
+        }
+
+        // Retried step: previous attempts come before the current commands
+        let mut children: Vec<Node> = vec![];
+        if let Some(retry) = e.get_retry() {
+            let attempt = e.get_attempt();
+            if let Some(status) = &e.status {
+                head.push_str(&format!(
+                    " ({} on attempt {}/{})",
+                    String::from(status),
+                    attempt,
+                    retry.attempts
+                ));
+            }
+            for (i, previous) in e.attempts.clone().unwrap_or_default().iter().enumerate() {
+                let mut node = Node::from(previous);
+                node.value = Some(format!("attempt {}/{}", i + 1, retry.attempts));
+                children.push(node);
+            }
+        }
+        children.extend(e.commands.iter().map(Node::from));
+
+        // Invoked pipeline
+        if let Some(child) = &e.child {
+            children.push(Node::from(child.as_ref()));
+        }
 
         // Duration
         let mut duration: Option<String> = None;
@@ -210,38 +232,8 @@ impl From<&Step> for Node {
         }
 
         // Fallback
-        if e.fallback.is_some() {
-            if e.fallback.clone().unwrap().on_failure.is_some() {
-                let on_failure = e.fallback.clone().unwrap().on_failure.unwrap();
-
-                let on_failure_children = on_failure.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_failure_children),
-                    value: Some("on_failure".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
-            if e.fallback.clone().unwrap().on_success.is_some() {
-                let on_success = e.fallback.clone().unwrap().on_success.unwrap();
-                let on_success_children = on_success.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_success_children),
-                    value: Some("on_success".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
-            if e.fallback.clone().unwrap().on_abortion.is_some() {
-                let on_abortion = e.fallback.clone().unwrap().on_abortion.unwrap();
-                let on_abortion_children = on_abortion.iter().map(Node::from).collect();
-                let node = Node {
-                    children: Some(on_abortion_children),
-                    value: Some("on_abortion".to_owned()),
-                    ..Node::default()
-                };
-                children.push(node);
-            }
+        if let Some(fallback) = &e.fallback {
+            children.extend(Vec::<Node>::from(fallback));
         }
         Node {
             value: Some(head),
@@ -253,6 +245,25 @@ impl From<&Step> for Node {
     }
 }
 
+impl From<&Attempt> for Node {
+    fn from(e: &Attempt) -> Self {
+        let children: Vec<Node> = e.commands.iter().map(Node::from).collect();
+
+        // Duration
+        let mut duration: Option<String> = None;
+        if e.duration.is_some() {
+            duration = Some(String::from(e.duration.as_ref().unwrap()));
+        }
+        Node {
+            value: Some("attempt".to_owned()),
+            status: e.status.clone(),
+            duration,
+            children: Some(children),
+            level: LevelFilter::Warn,
+        }
+    }
+}
+
 impl From<&Command> for Node {
     fn from(e: &Command) -> Self {
         // Duration
@@ -278,9 +289,18 @@ impl From<&Command> for Node {
 
             let out = match e.get_status() {
                 Some(Status::Succeeded) => e.process.io.stdout.clone(),
-                Some(Status::Failed) => e.process.io.stderr.clone(),
+                Some(Status::Failed) => match &e.process.state.reason {
+                    Some(reason) => Some(format!(
+                        "{}\n{}",
+                        reason,
+                        e.process.io.stderr.clone().unwrap_or_default()
+                    )),
+                    None => e.process.io.stderr.clone(),
+                },
+                Some(Status::TimedOut) => e.process.io.stderr.clone(),
                 Some(Status::Started) => None,
                 Some(Status::Aborted) => None,
+                Some(Status::Skipped) => None,
                 Some(Status::Running) => None,
                 None => None,
             };
@@ -104,4 +104,12 @@ impl Trigger {
 
         Ok(env)
     }
+    /**
+    Set the triggering environment to a recorded one,
+    to repeat a run as it was triggered.
+    */
+    pub fn restore(env: &Trigger) -> Result<Trigger> {
+        *TRIGGER_ENV.lock().unwrap() = env.clone();
+        Ok(env.clone())
+    }
 }
@@ -2,11 +2,12 @@
 use log::LevelFilter;
 pub use pipelight_exec::dates::Duration;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 // Structs
 use pipelight_exec::Process;
-pub use pipelight_exec::Status;
+pub use pipelight_exec::{Cancel, Limits, Sandbox, Status};
 use pipelight_utils::git::{Flag, Special};
 
 // Event - Process
@@ -25,12 +26,30 @@ pub struct ConfigOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<LevelFilter>,
+    // Environment variables of every pipelines commands
+    pub env: Option<BTreeMap<String, String>>,
+    // Maximum number of steps running at the same time, per pipeline run
+    pub max_jobs: Option<usize>,
 }
 
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct Config {
     pub pipelines: Option<Vec<Pipeline>>,
     pub options: Option<ConfigOpts>,
+    // Secret names and where to read their values from
+    pub secrets: Option<BTreeMap<String, Secret>>,
+}
+
+/**
+Where to read a secret value from.
+Values are never stored in the structs, so that they can't end up in the logs.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Secret {
+    // Name of the environment variable that holds the value
+    pub env: Option<String>,
+    // Absolute path of the file that holds the value
+    pub file: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -62,6 +81,28 @@ pub struct PipelineOpts {
     // when triggered by a git hook.
     pub attach: Option<bool>,
     pub log_level: Option<LevelFilter>,
+    // Resolved environment variables of the pipeline commands
+    pub env: Option<BTreeMap<String, String>>,
+    // What to do when an instance of the pipeline is already running
+    pub concurrency: Option<Concurrency>,
+}
+
+/**
+How a triggered pipeline behaves
+when an instance with the same name is already running.
+*/
+#[derive(Default, Debug, EnumIter, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Concurrency {
+    // Don't run, and log the run as skipped.
+    #[default]
+    Skip,
+    // Wait for the running instances to finish.
+    Queue,
+    // Stop the running instances first.
+    CancelPrevious,
+    // Run alongside the running instances.
+    Allow,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -74,11 +115,15 @@ pub struct Pipeline {
     pub triggers: Option<Vec<Trigger>>,
     pub fallback: Option<Fallback>,
     pub steps: Vec<StepOrParallel>,
+    // Maximum execution time (ex: "1h30m")
+    pub timeout: Option<String>,
     pub options: Option<PipelineOpts>,
+    // The run this one repeats or resumes
+    pub origin: Option<Uuid>,
 }
 impl Default for Pipeline {
     fn default() -> Self {
-        let steps = vec![StepOrParallel::Step(Step::default())];
+        let steps = vec![StepOrParallel::Step(Box::default())];
         Pipeline {
             uuid: Uuid::new_v4(),
             name: "default".to_owned(),
@@ -88,7 +133,9 @@ impl Default for Pipeline {
             triggers: None,
             options: None,
             steps,
+            timeout: None,
             fallback: None,
+            origin: None,
         }
     }
 }
@@ -98,35 +145,101 @@ pub struct StepOpts {
     // The step's command execution behavior
     // Failure Handling mode
     pub mode: Option<Mode>,
+    // Re-run the step commands on failure
+    pub retry: Option<Retry>,
+    // Resolved environment variables of the step commands
+    pub env: Option<BTreeMap<String, String>>,
+    // Absolute working directory of the step commands
+    pub cwd: Option<String>,
+    // Isolation of the step commands from the host
+    pub sandbox: Option<Sandbox>,
+    // Resource limits of the step commands
+    pub limits: Option<Limits>,
+}
+
+/**
+Retry policy of a failing step.
+*/
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Retry {
+    // Maximum number of executions, the first one included
+    pub attempts: u32,
+    // Time to wait before the next attempt (ex: "5s")
+    pub delay: Option<String>,
+    pub backoff: Backoff,
+}
+
+/**
+How the delay grows between two attempts.
+*/
+#[derive(Default, Debug, EnumIter, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backoff {
+    #[default]
+    Constant,
+    Linear,
+    Exponential,
+}
+
+/**
+A failed execution of a retried step.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Attempt {
+    pub status: Option<Status>,
+    pub duration: Option<Duration>,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct ParallelOpts {
+    // Maximum number of steps running at the same time
+    pub max_parallel: Option<usize>,
+    // Stop launching steps as soon as one fails
+    pub fail_fast: Option<bool>,
+    // Failure Handling mode
+    // StopOnFailure also cancels the running steps
+    pub mode: Option<Mode>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(untagged)]
 pub enum StepOrParallel {
-    Step(Step),
-    Parallel(Parallel),
+    Step(Box<Step>),
+    Parallel(Box<Parallel>),
 }
 impl Default for StepOrParallel {
     fn default() -> Self {
-        let step = Step::default();
+        let step = Box::default();
         StepOrParallel::Step(step)
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct Parallel {
+    // Name of the matrix step the block has been expanded from
+    pub name: Option<String>,
+    // Steps that must be done before this one starts
+    pub needs: Option<Vec<String>>,
     pub status: Option<Status>,
     pub duration: Option<Duration>,
     pub steps: Vec<Step>,
+    // Maximum execution time (ex: "10m")
+    pub timeout: Option<String>,
+    pub options: Option<ParallelOpts>,
     // Fallback Hooks
     pub fallback: Option<Fallback>,
 }
 impl Default for Parallel {
     fn default() -> Self {
         Parallel {
+            name: None,
+            needs: None,
             status: None,
             duration: None,
             steps: vec![Step::default()],
+            timeout: None,
+            options: None,
             fallback: None,
         }
     }
@@ -138,10 +251,34 @@ pub struct Step {
     pub status: Option<Status>,
     pub duration: Option<Duration>,
     pub commands: Vec<Command>,
+    // Steps that must be done before this one starts
+    pub needs: Option<Vec<String>>,
+    // Maximum execution time (ex: "10m")
+    pub timeout: Option<String>,
+    // Condition to run the step
+    #[serde(rename = "if")]
+    pub condition: Option<String>,
+    // Failed attempts preceding the current commands execution
+    pub attempts: Option<Vec<Attempt>>,
+    // Files kept in the artifact store after the run
+    pub artifacts: Option<Artifacts>,
+    // Files the step result depends on
+    pub inputs: Option<Inputs>,
+    // Pipeline run instead of commands
+    pub pipeline: Option<String>,
+    // The invoked pipeline run, nested under the step
+    pub child: Option<Box<Pipeline>>,
+    // Whether the step result was reused from the original run
+    pub reused: Option<bool>,
+    // Values written by the commands to $PIPELIGHT_OUTPUT
+    pub outputs: Option<BTreeMap<String, String>>,
     // Failure Handling mode
     pub options: Option<StepOpts>,
     // Fallback Hooks
     pub fallback: Option<Fallback>,
+    // Set when the running step is aborted
+    #[serde(skip)]
+    pub cancelled: Cancelled,
 }
 impl Default for Step {
     fn default() -> Self {
@@ -151,18 +288,83 @@ impl Default for Step {
             status: None,
             duration: None,
             commands,
+            needs: None,
+            timeout: None,
+            condition: None,
+            attempts: None,
+            artifacts: None,
+            inputs: None,
+            pipeline: None,
+            child: None,
+            reused: None,
+            outputs: None,
             options: None,
             fallback: None,
+            cancelled: Cancelled::default(),
         }
     }
 }
 
+/**
+The cancellation flags of a step.
+The flags are atomics, raised from any thread (ex: a failing sibling step)
+and watched by the step own running commands.
+They belong to a single step run:
+they are neither serialized nor compared, and a cloned step starts uncancelled.
+*/
+#[derive(Debug, Default)]
+pub struct Cancelled {
+    // The step own flag
+    pub(crate) flag: Cancel,
+    // The flags of the enclosing execution (pipeline abortion, invoking step)
+    pub(crate) watched: Vec<Cancel>,
+}
+impl Clone for Cancelled {
+    fn clone(&self) -> Self {
+        Cancelled::default()
+    }
+}
+impl PartialEq for Cancelled {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+impl Eq for Cancelled {}
+
+/**
+Files declared as step outputs.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Artifacts {
+    // Declared glob patterns
+    pub globs: Vec<String>,
+    // Collected files, relative to the step artifact directory
+    pub files: Option<Vec<String>>,
+}
+
+/**
+Files the step result depends on, used to compute the step cache key.
+*/
+#[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+pub struct Inputs {
+    // Declared glob patterns
+    pub globs: Vec<String>,
+    // Hash of the inputs, commands and environment
+    pub key: Option<String>,
+    // Whether the step result was restored from the cache
+    pub cached: Option<bool>,
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct Fallback {
     pub on_started: Option<Vec<StepOrParallel>>,
     pub on_failure: Option<Vec<StepOrParallel>>,
     pub on_success: Option<Vec<StepOrParallel>>,
     pub on_abortion: Option<Vec<StepOrParallel>>,
+    // Run after the others, whatever the outcome
+    pub on_finished: Option<Vec<StepOrParallel>>,
+    // The on_finished result, apart from the pipeline or step status
+    pub on_finished_status: Option<Status>,
 }
 
 #[derive(Debug, EnumIter, Serialize, Deserialize, Clone, PartialEq, PartialOrd, Eq, Ord)]